@@ -1,10 +1,32 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use colored::Colorize;
 use comfy_table::{Cell, Color, Table, presets::UTF8_FULL};
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
 use jiff::Timestamp;
+use std::collections::BTreeSet;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::{OutputFormat, client::RicochetClient, config::Config, item::resolve_id, utils};
+use crate::{
+    OutputFormat,
+    client::RicochetClient,
+    config::Config,
+    item::{deployment::POLL_INTERVAL, resolve_id},
+    utils,
+};
+
+/// The instance IDs in a `list_instances` response.
+pub(crate) fn instance_ids(instances: &serde_json::Value) -> BTreeSet<String> {
+    instances
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|i| i.get("instance_id").and_then(|v| v.as_str()))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
 pub async fn list_instances(
     config: &Config,
@@ -151,3 +173,102 @@ pub async fn stop_instance(
 
     Ok(())
 }
+
+/// Restart an app's instances by stopping them and waiting for the server to
+/// start replacements. With `rolling`, instances are stopped `batch` at a
+/// time and each batch waits for its replacements, so the app keeps serving.
+pub async fn restart_instances(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    rolling: bool,
+    batch: usize,
+    timeout: Duration,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let old = instance_ids(&client.list_instances(&id).await?);
+    if old.is_empty() {
+        println!("{}", "No instances to restart".yellow());
+        return Ok(());
+    }
+    let batch = if rolling { batch.max(1) } else { old.len() };
+
+    let pb = ProgressBar::new(old.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} {msg} [{bar:20.green}] {pos}/{len} [{elapsed}]")?
+            .progress_chars("=> "),
+    );
+    pb.enable_steady_tick(Duration::from_millis(80));
+
+    let result = restart_batches(&client, &id, &old, batch, timeout, &pb).await;
+    pb.finish_and_clear();
+    result?;
+
+    println!(
+        "{} Restarted {} instance(s) of {}",
+        "✓".green().bold(),
+        old.len(),
+        id.bright_cyan()
+    );
+
+    Ok(())
+}
+
+async fn restart_batches(
+    client: &RicochetClient,
+    id: &str,
+    old: &BTreeSet<String>,
+    batch: usize,
+    timeout: Duration,
+    pb: &ProgressBar,
+) -> Result<()> {
+    let old_ids: Vec<&String> = old.iter().collect();
+    // Every instance seen so far, so that replacements from earlier batches
+    // and instances the autoscaler started are not taken for this batch's
+    let mut known = old.clone();
+
+    for chunk in old_ids.chunks(batch) {
+        let names: Vec<&str> = chunk.iter().map(|s| s.as_str()).collect();
+        pb.set_message(format!("Stopping {}", names.join(", ").bright_cyan()));
+        for pid in chunk {
+            client.stop_instance(id, pid).await?;
+        }
+
+        // Every stopped instance needs a replacement that was not running
+        // before this batch
+        pb.set_message("Waiting for replacements".to_string());
+        let deadline = Instant::now() + timeout;
+        loop {
+            let running = instance_ids(&client.list_instances(id).await?);
+            if running.difference(&known).count() >= chunk.len() {
+                known.extend(running);
+                break;
+            }
+            if Instant::now() >= deadline {
+                bail!(
+                    "No replacement for {} started within {}. Apps with min_instances = 0 only start instances when they receive traffic",
+                    names.join(", "),
+                    HumanDuration(timeout)
+                );
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        pb.inc(chunk.len() as u64);
+        for pid in &names {
+            pb.println(format!(
+                "{} Instance {} replaced",
+                "✓".green().bold(),
+                pid.bright_cyan()
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use jiff::{SignedDuration, Timestamp};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::Path;
+
+use crate::{
+    OutputFormat,
+    app::instances::instance_ids,
+    client::RicochetClient,
+    config::Config,
+    item::{deployment::POLL_INTERVAL, resolve_id},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// One line written by an instance, as returned by the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    pub timestamp: Timestamp,
+    #[serde(default)]
+    pub stream: LogStream,
+    pub line: String,
+}
+
+/// A log line tagged with the instance that wrote it.
+#[derive(Debug, Serialize)]
+struct InstanceLine {
+    instance_id: String,
+    #[serde(flatten)]
+    line: LogLine,
+}
+
+/// Read `--since` as either a duration back from `now` (`10m`, `1h30m`) or
+/// an absolute timestamp.
+pub fn parse_since(since: &str, now: Timestamp) -> Result<Timestamp> {
+    if let Ok(ts) = since.parse::<Timestamp>() {
+        return Ok(ts);
+    }
+    let ago: SignedDuration = since.parse().with_context(|| {
+        format!("Invalid --since '{since}', expected a duration like 10m or a timestamp")
+    })?;
+    Ok(now.checked_sub(ago.abs())?)
+}
+
+/// How far an instance's log has been printed. The server includes lines
+/// written exactly at `since`, so the lines sharing the newest timestamp are
+/// counted to skip them on the next fetch.
+#[derive(Debug, Default)]
+struct Cursor {
+    last: Option<Timestamp>,
+    at_last: usize,
+}
+
+impl Cursor {
+    /// Drop the lines of a fetch that were already printed and move the
+    /// cursor past the rest.
+    fn advance(&mut self, lines: Vec<LogLine>) -> Vec<LogLine> {
+        let last = self.last;
+        let mut skip = self.at_last;
+        let fresh: Vec<LogLine> = lines
+            .into_iter()
+            .filter(|l| match last {
+                Some(last) if l.timestamp < last => false,
+                Some(last) if l.timestamp == last && skip > 0 => {
+                    skip -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+
+        for l in &fresh {
+            if self.last == Some(l.timestamp) {
+                self.at_last += 1;
+            } else {
+                self.last = Some(l.timestamp);
+                self.at_last = 1;
+            }
+        }
+        fresh
+    }
+}
+
+fn print_line(l: &InstanceLine, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(l)?),
+        OutputFormat::Yaml => println!("---\n{}", serde_yaml::to_string(l)?.trim_end()),
+        OutputFormat::Table => {
+            let prefix = format!("[{}]", l.instance_id).bright_cyan();
+            match l.line.stream {
+                LogStream::Stdout => println!("{} {}", prefix, l.line.line),
+                LogStream::Stderr => println!("{} {}", prefix, l.line.line.red()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the logs of an app's running instances, interleaved by time and
+/// prefixed with the instance ID. With `follow`, keep polling for new lines
+/// and pick up instances as they start.
+#[allow(clippy::too_many_arguments)]
+pub async fn logs(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    instance: Option<&str>,
+    follow: bool,
+    since: Option<&str>,
+    grep: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let since = since
+        .map(|s| parse_since(s, Timestamp::now()))
+        .transpose()?;
+    let grep = grep
+        .map(Regex::new)
+        .transpose()
+        .context("Invalid --grep pattern")?;
+
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let mut cursors: HashMap<String, Cursor> = HashMap::new();
+    let mut running: BTreeSet<String> = BTreeSet::new();
+    let mut first = true;
+
+    loop {
+        let pids = match instance {
+            Some(pid) => BTreeSet::from([pid.to_string()]),
+            None => instance_ids(&client.list_instances(&id).await?),
+        };
+        if first && pids.is_empty() {
+            if follow {
+                eprintln!("{}", "No running instances yet, waiting...".yellow());
+            } else {
+                match format {
+                    OutputFormat::Table => println!("{}", "No running instances".yellow()),
+                    OutputFormat::Json | OutputFormat::Yaml => println!("[]"),
+                }
+                return Ok(());
+            }
+        }
+        if follow && matches!(format, OutputFormat::Table) {
+            for pid in pids.difference(&running).filter(|_| !first) {
+                eprintln!(
+                    "{} Instance {} started",
+                    "→".bright_cyan(),
+                    pid.bright_cyan()
+                );
+            }
+            for pid in running.difference(&pids) {
+                eprintln!(
+                    "{} Instance {} exited",
+                    "→".bright_cyan(),
+                    pid.bright_cyan()
+                );
+            }
+        }
+
+        let mut batch = Vec::new();
+        for pid in &pids {
+            let cursor = cursors.entry(pid.clone()).or_default();
+            let lines = match client
+                .get_instance_logs(&id, pid, cursor.last.or(since))
+                .await
+            {
+                Ok(lines) => lines,
+                // The instance may have exited since it was listed; the next
+                // poll reports that. Any other failure is an error.
+                Err(e) if follow && instance.is_none() => {
+                    if instance_ids(&client.list_instances(&id).await?).contains(pid) {
+                        return Err(e);
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            batch.extend(
+                cursor
+                    .advance(lines)
+                    .into_iter()
+                    .filter(|l| grep.as_ref().is_none_or(|re| re.is_match(&l.line)))
+                    .map(|line| InstanceLine {
+                        instance_id: pid.clone(),
+                        line,
+                    }),
+            );
+        }
+        batch.sort_by_key(|l| l.line.timestamp);
+
+        if !follow {
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&batch)?),
+                OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&batch)?),
+                OutputFormat::Table => {
+                    for l in &batch {
+                        print_line(l, format)?;
+                    }
+                }
+            }
+            return Ok(());
+        }
+
+        for l in &batch {
+            print_line(l, format)?;
+        }
+        std::io::stdout().flush()?;
+
+        running = pids;
+        first = false;
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(ts: &str, text: &str) -> LogLine {
+        LogLine {
+            timestamp: ts.parse().unwrap(),
+            stream: LogStream::Stdout,
+            line: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_since_accepts_durations_and_timestamps() {
+        let now: Timestamp = "2025-01-06T08:00:00Z".parse().unwrap();
+
+        assert_eq!(
+            parse_since("10m", now).unwrap().to_string(),
+            "2025-01-06T07:50:00Z"
+        );
+        assert_eq!(
+            parse_since("1h30m", now).unwrap().to_string(),
+            "2025-01-06T06:30:00Z"
+        );
+        assert_eq!(
+            parse_since("2025-01-05T00:00:00Z", now)
+                .unwrap()
+                .to_string(),
+            "2025-01-05T00:00:00Z"
+        );
+        assert!(parse_since("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn cursor_skips_lines_already_printed() {
+        let mut cursor = Cursor::default();
+        let first = cursor.advance(vec![
+            line("2025-01-06T08:00:00Z", "a"),
+            line("2025-01-06T08:00:01Z", "b"),
+            line("2025-01-06T08:00:01Z", "c"),
+        ]);
+        assert_eq!(first.len(), 3);
+
+        // The next fetch starts at the newest timestamp, repeating b and c
+        let second = cursor.advance(vec![
+            line("2025-01-06T08:00:01Z", "b"),
+            line("2025-01-06T08:00:01Z", "c"),
+            line("2025-01-06T08:00:01Z", "d"),
+            line("2025-01-06T08:00:02Z", "e"),
+        ]);
+        let texts: Vec<&str> = second.iter().map(|l| l.line.as_str()).collect();
+        assert_eq!(texts, vec!["d", "e"]);
+
+        assert!(
+            cursor
+                .advance(vec![line("2025-01-06T08:00:02Z", "e")])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn log_line_defaults_to_stdout() {
+        let l: LogLine =
+            serde_json::from_str(r#"{"timestamp": "2025-01-06T08:00:00Z", "line": "hi"}"#).unwrap();
+        assert_eq!(l.stream, LogStream::Stdout);
+    }
+}
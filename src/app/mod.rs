@@ -1 +1,13 @@
+use anyhow::Result;
+use url::Url;
+
 pub mod instances;
+pub mod logs;
+pub mod proxy;
+pub mod scale;
+pub mod smoke;
+
+/// Where an app is served on a Ricochet server.
+pub(crate) fn app_url(server: &Url, id: &str) -> Result<Url> {
+    Ok(server.join(&format!("/apps/{id}/"))?)
+}
@@ -0,0 +1,467 @@
+use anyhow::{Context, Result};
+use axum::{
+    Router,
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use colored::Colorize;
+use hyper_util::rt::TokioIo;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use url::Url;
+
+use crate::{app::app_url, client::RicochetClient, config::Config, item::resolve_id};
+
+/// Headers that describe a single connection and must not be forwarded.
+/// `connection` and `upgrade` are put back for websocket handshakes.
+const HOP_BY_HOP: &[header::HeaderName] = &[
+    header::CONNECTION,
+    header::HOST,
+    header::PROXY_AUTHENTICATE,
+    header::PROXY_AUTHORIZATION,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+];
+
+struct ProxyState {
+    client: reqwest::Client,
+    target: Url,
+    api_key: String,
+}
+
+/// Map a local request path onto the app's URL.
+fn upstream_url(target: &Url, path_and_query: &str) -> Result<Url> {
+    Ok(target.join(path_and_query.trim_start_matches('/'))?)
+}
+
+fn copy_headers(from: &HeaderMap) -> HeaderMap {
+    let mut to = from.clone();
+    for name in HOP_BY_HOP {
+        to.remove(name);
+    }
+    // `keep-alive` has no named constant
+    to.remove("keep-alive");
+    to
+}
+
+fn is_upgrade(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::UPGRADE)
+        && headers
+            .get(header::CONNECTION)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.to_ascii_lowercase().contains("upgrade"))
+}
+
+/// Point redirects to the app back at the proxy so the browser stays on it.
+/// Both absolute URLs and path-absolute locations under the app's path are
+/// rewritten.
+fn rewrite_location(headers: &mut HeaderMap, target: &Url) {
+    let Some(location) = headers.get(header::LOCATION).and_then(|v| v.to_str().ok()) else {
+        return;
+    };
+    if let Some(rest) = location
+        .strip_prefix(target.as_str())
+        .or_else(|| location.strip_prefix(target.path()))
+        && let Ok(local) = HeaderValue::from_str(&format!("/{rest}"))
+    {
+        headers.insert(header::LOCATION, local);
+    }
+}
+
+/// Whether `authority` (a `Host` value or an origin's host and port) names
+/// this machine's loopback interface.
+fn is_loopback(authority: &str) -> bool {
+    let name = match authority.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => authority,
+    };
+    matches!(name, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Whether a request was made to the proxy by name, from a local page or
+/// outside a browser. Any web page can reach 127.0.0.1, either directly or
+/// through DNS rebinding, and must not get the API key attached.
+fn is_local_request(headers: &HeaderMap) -> bool {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+    };
+    let host_ok = header(header::HOST).is_some_and(is_loopback);
+    let origin_ok = header(header::ORIGIN).is_none_or(|origin| {
+        Url::parse(origin)
+            .ok()
+            .and_then(|url| url.host_str().map(is_loopback))
+            .unwrap_or(false)
+    });
+    host_ok && origin_ok
+}
+
+async fn forward(State(state): State<Arc<ProxyState>>, req: Request) -> Response {
+    if !is_local_request(req.headers()) {
+        return (
+            StatusCode::FORBIDDEN,
+            "ricochet proxy: only requests to localhost from local pages are forwarded\n",
+        )
+            .into_response();
+    }
+    match forward_request(&state, req).await {
+        Ok(response) => response,
+        Err(e) => (StatusCode::BAD_GATEWAY, format!("ricochet proxy: {e:#}\n")).into_response(),
+    }
+}
+
+async fn forward_request(state: &ProxyState, mut req: Request) -> Result<Response> {
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map_or("/", |pq| pq.as_str())
+        .to_string();
+    let url = upstream_url(&state.target, &path_and_query)?;
+    let upgrade = is_upgrade(req.headers());
+
+    let mut headers = copy_headers(req.headers());
+    headers.insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_str(&format!("Key {}", state.api_key))?,
+    );
+    if upgrade {
+        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        if let Some(protocol) = req.headers().get(header::UPGRADE) {
+            headers.insert(header::UPGRADE, protocol.clone());
+        }
+    }
+
+    let on_upgrade = upgrade.then(|| hyper::upgrade::on(&mut req));
+    let method = req.method().clone();
+    let body = reqwest::Body::wrap_stream(req.into_body().into_data_stream());
+
+    let upstream = state
+        .client
+        .request(method, url)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await?;
+
+    let status = upstream.status();
+    let mut response_headers = copy_headers(upstream.headers());
+    rewrite_location(&mut response_headers, &state.target);
+
+    if let Some(on_upgrade) = on_upgrade
+        && status == StatusCode::SWITCHING_PROTOCOLS
+    {
+        // Answer the handshake ourselves, then splice the two connections
+        // together once both sides have switched protocols
+        response_headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        if let Some(protocol) = upstream.headers().get(header::UPGRADE) {
+            response_headers.insert(header::UPGRADE, protocol.clone());
+        }
+        tokio::spawn(async move {
+            let (Ok(mut upstream), Ok(downstream)) = (upstream.upgrade().await, on_upgrade.await)
+            else {
+                return;
+            };
+            let mut downstream = TokioIo::new(downstream);
+            let _ = tokio::io::copy_bidirectional(&mut downstream, &mut upstream).await;
+        });
+
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = status;
+        *response.headers_mut() = response_headers;
+        return Ok(response);
+    }
+
+    let mut response = Response::new(Body::from_stream(upstream.bytes_stream()));
+    *response.status_mut() = status;
+    *response.headers_mut() = response_headers;
+    Ok(response)
+}
+
+fn router(target: Url, api_key: String) -> Result<Router> {
+    // Redirects go back to the caller, and websockets stay open as long as
+    // they like, so neither is handled by the client
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let state = Arc::new(ProxyState {
+        client,
+        target,
+        api_key,
+    });
+    Ok(Router::new().fallback(forward).with_state(state))
+}
+
+/// Serve a deployed app on a local port, adding the stored API key to every
+/// request, including websocket handshakes. Only listens on loopback and only
+/// forwards requests addressed to it, since anyone who can reach the port
+/// acts with that key.
+pub async fn proxy(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    port: u16,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let target = app_url(&server_config.url, &id)?;
+    let api_key = server_config
+        .api_key
+        .clone()
+        .context("No API key configured. Use 'ricochet login' to authenticate")?;
+
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("binding to port {port}"))?;
+    let local = format!("http://{}", listener.local_addr()?);
+
+    println!(
+        "{} Proxying {} → {}",
+        "✓".green().bold(),
+        local.bright_cyan(),
+        target.as_str().dimmed()
+    );
+    println!("  Press Ctrl+C to stop");
+
+    axum::serve(listener, router(target, api_key)?)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upstream_url_keeps_path_and_query() {
+        let target = app_url(
+            &Url::parse("https://ricochet.example.com").unwrap(),
+            "01ABC",
+        )
+        .unwrap();
+
+        assert_eq!(
+            upstream_url(&target, "/").unwrap().as_str(),
+            "https://ricochet.example.com/apps/01ABC/"
+        );
+        assert_eq!(
+            upstream_url(&target, "/api/items?page=2").unwrap().as_str(),
+            "https://ricochet.example.com/apps/01ABC/api/items?page=2"
+        );
+    }
+
+    #[test]
+    fn copy_headers_drops_hop_by_hop() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("localhost:8080"));
+        headers.insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
+        headers.insert("keep-alive", HeaderValue::from_static("timeout=5"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/html"));
+
+        let copied = copy_headers(&headers);
+
+        assert_eq!(copied.len(), 1);
+        assert_eq!(copied[header::ACCEPT], "text/html");
+    }
+
+    #[test]
+    fn detects_websocket_handshake() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            header::CONNECTION,
+            HeaderValue::from_static("keep-alive, Upgrade"),
+        );
+        assert!(is_upgrade(&headers));
+
+        headers.remove(header::UPGRADE);
+        assert!(!is_upgrade(&headers));
+    }
+
+    #[test]
+    fn rewrites_redirects_into_the_app() {
+        let target = app_url(
+            &Url::parse("https://ricochet.example.com").unwrap(),
+            "01ABC",
+        )
+        .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::LOCATION,
+            HeaderValue::from_static("https://ricochet.example.com/apps/01ABC/login"),
+        );
+
+        rewrite_location(&mut headers, &target);
+
+        assert_eq!(headers[header::LOCATION], "/login");
+    }
+
+    #[test]
+    fn rewrites_path_absolute_redirects_into_the_app() {
+        let target = app_url(
+            &Url::parse("https://ricochet.example.com").unwrap(),
+            "01ABC",
+        )
+        .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::LOCATION,
+            HeaderValue::from_static("/apps/01ABC/login?next=%2F"),
+        );
+
+        rewrite_location(&mut headers, &target);
+
+        assert_eq!(headers[header::LOCATION], "/login?next=%2F");
+
+        // Redirects elsewhere on the server are left alone
+        headers.insert(header::LOCATION, HeaderValue::from_static("/oauth/login"));
+        rewrite_location(&mut headers, &target);
+        assert_eq!(headers[header::LOCATION], "/oauth/login");
+    }
+
+    #[test]
+    fn accepts_only_local_hosts_and_origins() {
+        let request = |host: &str, origin: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::HOST, HeaderValue::from_str(host).unwrap());
+            if let Some(origin) = origin {
+                headers.insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+            }
+            headers
+        };
+
+        assert!(is_local_request(&request("localhost:8080", None)));
+        assert!(is_local_request(&request("127.0.0.1:8080", None)));
+        assert!(is_local_request(&request("[::1]:8080", None)));
+        assert!(is_local_request(&request(
+            "localhost:8080",
+            Some("http://localhost:8080")
+        )));
+        // DNS rebinding keeps the attacker's name in Host
+        assert!(!is_local_request(&request("evil.example.com:8080", None)));
+        // A cross-site form post names the proxy but comes from elsewhere
+        assert!(!is_local_request(&request(
+            "127.0.0.1:8080",
+            Some("https://evil.example.com")
+        )));
+        assert!(!is_local_request(&request("127.0.0.1:8080", Some("null"))));
+        assert!(!is_local_request(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn refuses_requests_for_other_hosts() {
+        let mut upstream = mockito::Server::new_async().await;
+        let app_mock = upstream
+            .mock("GET", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let target = app_url(&Url::parse(&upstream.url()).unwrap(), "01ABC").unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(target, "test_api_key".to_string()).unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let response = reqwest::Client::new()
+            .get(format!("http://{addr}/"))
+            .header(header::HOST, "rebound.example.com")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        app_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn forwards_requests_with_the_api_key() {
+        let mut upstream = mockito::Server::new_async().await;
+        let _m = upstream
+            .mock("POST", "/apps/01ABC/predict")
+            .match_header("authorization", "Key test_api_key")
+            .match_query(mockito::Matcher::UrlEncoded("n".into(), "3".into()))
+            .match_body("[1, 2]")
+            .with_status(201)
+            .with_header("x-app", "yes")
+            .with_body("ok")
+            .create_async()
+            .await;
+
+        let target = app_url(&Url::parse(&upstream.url()).unwrap(), "01ABC").unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(target, "test_api_key".to_string()).unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{addr}/predict?n=3"))
+            .body("[1, 2]")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["x-app"], "yes");
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn splices_websocket_upgrades() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        // An upstream that accepts any upgrade and then echoes bytes back
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = upstream.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+            assert!(request.contains("authorization: key test_api_key"));
+            socket
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nConnection: upgrade\r\nUpgrade: websocket\r\n\r\n")
+                .await
+                .unwrap();
+            let n = socket.read(&mut buf).await.unwrap();
+            socket.write_all(&buf[..n]).await.unwrap();
+        });
+
+        let target = app_url(
+            &Url::parse(&format!("http://{upstream_addr}")).unwrap(),
+            "01ABC",
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(target, "test_api_key".to_string()).unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        socket
+            .write_all(b"GET /websocket/ HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = vec![0; 4096];
+        let n = socket.read(&mut buf).await.unwrap();
+        assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 101"));
+
+        socket.write_all(b"ping").await.unwrap();
+        let n = socket.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+    }
+}
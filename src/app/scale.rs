@@ -0,0 +1,318 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use dialoguer::{Confirm, theme::ColorfulTheme};
+use ricochet_core::content::ContentItem;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    OutputFormat,
+    client::RicochetClient,
+    config::Config,
+    item::{
+        load_local, resolve_id,
+        settings::{PatchBuilder, format_change_rows},
+    },
+    utils,
+};
+
+/// The `[serve]` values `app scale` can change. Unset fields keep their
+/// deployed value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Scale {
+    pub min_instances: Option<u32>,
+    pub max_instances: Option<u32>,
+    pub spawn_threshold: Option<u32>,
+}
+
+impl Scale {
+    fn is_empty(&self) -> bool {
+        self.min_instances.is_none()
+            && self.max_instances.is_none()
+            && self.spawn_threshold.is_none()
+    }
+
+    /// The `(key, value)` pairs that were given, in `[serve]` order.
+    fn values(&self) -> Vec<(&'static str, u32)> {
+        [
+            ("min_instances", self.min_instances),
+            ("max_instances", self.max_instances),
+            ("spawn_threshold", self.spawn_threshold),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|v| (key, v)))
+        .collect()
+    }
+}
+
+/// Set each `key = value` inside the `[serve]` table of a `_ricochet.toml`,
+/// leaving every other line as written, and a rewritten line's trailing
+/// comment in place. Keys that are missing are added at the end of the table.
+fn write_serve_values(toml: &str, values: &[(&str, u32)]) -> String {
+    let mut lines: Vec<String> = toml.lines().map(String::from).collect();
+
+    let Some(header) = lines.iter().position(|l| l.trim() == "[serve]") else {
+        return toml.to_string();
+    };
+    let end = lines[header + 1..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| header + 1 + i);
+
+    let mut missing = Vec::new();
+    for (key, value) in values {
+        let existing = lines[header + 1..end].iter().position(|l| {
+            l.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        });
+        match existing {
+            Some(i) => {
+                let line = &mut lines[header + 1 + i];
+                let indent = &line[..line.len() - line.trim_start().len()];
+                // The values are numbers, so a `#` can only start a comment
+                let comment = line.find('#').map_or("", |i| {
+                    let before = &line[..i];
+                    &line[before.trim_end().len()..]
+                });
+                *line = format!("{indent}{key} = {value}{comment}");
+            }
+            None => missing.push(format!("{key} = {value}")),
+        }
+    }
+
+    // Add new keys after the table's last non-blank line
+    let insert_at = (header + 1..end)
+        .rev()
+        .find(|&i| !lines[i].trim().is_empty())
+        .map_or(header + 1, |i| i + 1);
+    lines.splice(insert_at..insert_at, missing);
+
+    let mut out = lines.join("\n");
+    if toml.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// Change an app's instance limits without touching its other settings.
+#[allow(clippy::too_many_arguments)]
+pub async fn scale(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    scale: Scale,
+    force: bool,
+    write_toml: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if scale.is_empty() {
+        bail!("Nothing to change. Pass --min, --max or --spawn-threshold");
+    }
+
+    let id = resolve_id(id, path)?;
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let remote_toml = client.get_ricochet_toml(&id).await?;
+    let remote = ContentItem::from_toml(&remote_toml).context("parsing remote _ricochet.toml")?;
+    let current = remote.serve.clone().unwrap_or_default();
+
+    let min = scale.min_instances.unwrap_or(current.min_instances);
+    let max = scale.max_instances.unwrap_or(current.max_instances);
+    if min > max {
+        bail!("min_instances ({min}) cannot be greater than max_instances ({max})");
+    }
+
+    let mut b = PatchBuilder::new();
+    b.field(
+        "serve",
+        "min_instances",
+        json!(current.min_instances),
+        json!(min),
+    );
+    b.field(
+        "serve",
+        "max_instances",
+        json!(current.max_instances),
+        json!(max),
+    );
+    if let Some(threshold) = scale.spawn_threshold {
+        b.field(
+            "serve",
+            "spawn_threshold",
+            json!(current.spawn_threshold),
+            json!(threshold),
+        );
+    }
+    let (changes, patch) = b.finish();
+
+    if changes.is_empty() {
+        eprintln!(
+            "{} {} is already scaled to {}–{} instances",
+            "✓".green().bold(),
+            id.bright_cyan(),
+            min,
+            max
+        );
+    } else {
+        eprintln!(
+            "Scaling {} ({})\n\n{}\n",
+            id.bright_cyan(),
+            remote.content.name,
+            format_change_rows(&changes)
+        );
+
+        if !force && !utils::confirm("Apply these changes?")? {
+            eprintln!("{}", "Scale cancelled".yellow());
+            return Ok(());
+        }
+
+        client
+            .update_settings(&id, &patch)
+            .await
+            .context("sending settings update")?;
+
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&patch)?),
+            OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&patch)?),
+            OutputFormat::Table => {
+                println!("{} Scaled {}", "✓".green().bold(), id.bright_cyan())
+            }
+        }
+    }
+
+    save_to_local_toml(&id, path, &scale, force, write_toml)
+}
+
+/// Offer to write the new values into the local `_ricochet.toml` so the next
+/// `settings update` does not undo them. With `force` nothing is asked, and
+/// the file is only written if `write_toml` is set.
+fn save_to_local_toml(
+    id: &str,
+    path: Option<&Path>,
+    scale: &Scale,
+    force: bool,
+    write_toml: bool,
+) -> Result<()> {
+    // Only touch a toml that describes this item
+    let Ok((local_id, local)) = load_local(path) else {
+        return Ok(());
+    };
+    if local_id != id {
+        return Ok(());
+    }
+    // Without a [serve] table `settings update` leaves scaling alone, so
+    // there is nothing that could undo the change
+    let Some(serve) = &local.serve else {
+        return Ok(());
+    };
+    let values: Vec<(&str, u32)> = scale
+        .values()
+        .into_iter()
+        .filter(|(key, value)| {
+            let current = match *key {
+                "min_instances" => serve.min_instances,
+                "max_instances" => serve.max_instances,
+                _ => serve.spawn_threshold,
+            };
+            current != *value
+        })
+        .collect();
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let toml_path = path
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("_ricochet.toml"));
+    let write = write_toml
+        || (!force
+            && !utils::is_non_interactive()
+            && Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Save the new values to {}?", toml_path.display()))
+                .default(true)
+                .interact()?);
+    if !write {
+        eprintln!(
+            "{} {} still has the old values; {} would scale the app back",
+            "⚠".yellow(),
+            toml_path.display(),
+            "settings update".bright_cyan()
+        );
+        return Ok(());
+    }
+
+    let original = std::fs::read_to_string(&toml_path)?;
+    std::fs::write(&toml_path, write_serve_values(&original, &values))?;
+    eprintln!(
+        "{} Updated {}",
+        "✓".green().bold(),
+        toml_path.display().to_string().bright_cyan()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_serve_values_replaces_and_adds_keys() {
+        let toml = "[content]\nname = \"app\"\n\n[serve]\nmin_instances = 0\n  max_instances = 5\n\n[serve.k8s]\nmin_instances = 9\n";
+
+        let updated = write_serve_values(
+            toml,
+            &[
+                ("min_instances", 2),
+                ("max_instances", 6),
+                ("spawn_threshold", 70),
+            ],
+        );
+
+        assert_eq!(
+            updated,
+            "[content]\nname = \"app\"\n\n[serve]\nmin_instances = 2\n  max_instances = 6\nspawn_threshold = 70\n\n[serve.k8s]\nmin_instances = 9\n"
+        );
+    }
+
+    #[test]
+    fn write_serve_values_keeps_trailing_comments() {
+        let toml = "[serve]\nmin_instances = 0  # warm\nmax_instances = 5# cap\n";
+
+        let updated = write_serve_values(toml, &[("min_instances", 1), ("max_instances", 8)]);
+
+        assert_eq!(
+            updated,
+            "[serve]\nmin_instances = 1  # warm\nmax_instances = 8# cap\n"
+        );
+    }
+
+    #[test]
+    fn write_serve_values_does_not_match_key_prefixes() {
+        let toml = "[serve]\nmax_instances_hint = 1\n";
+
+        let updated = write_serve_values(toml, &[("max_instances", 4)]);
+
+        assert_eq!(
+            updated,
+            "[serve]\nmax_instances_hint = 1\nmax_instances = 4\n"
+        );
+    }
+
+    #[test]
+    fn scale_values_keep_serve_order() {
+        let scale = Scale {
+            min_instances: None,
+            max_instances: Some(6),
+            spawn_threshold: Some(50),
+        };
+        assert_eq!(
+            scale.values(),
+            vec![("max_instances", 6), ("spawn_threshold", 50)]
+        );
+        assert!(Scale::default().is_empty());
+    }
+}
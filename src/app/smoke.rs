@@ -0,0 +1,93 @@
+use anyhow::Result;
+use colored::Colorize;
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
+use std::time::{Duration, Instant};
+
+use crate::{client::RicochetClient, item::deployment::POLL_INTERVAL};
+
+/// Exit status when a deployment succeeded but its smoke check did not pass.
+pub const SMOKE_FAILED_EXIT_CODE: i32 = 3;
+
+/// A request the freshly deployed app must answer before the deploy counts
+/// as done.
+#[derive(Debug, Clone)]
+pub struct SmokeCheck {
+    /// Path within the app, e.g. `/health`
+    pub path: String,
+    pub expect_status: u16,
+    pub timeout: Duration,
+}
+
+/// A smoke check that did not pass in time. The CLI exits with
+/// [`SMOKE_FAILED_EXIT_CODE`].
+#[derive(Debug)]
+pub struct SmokeCheckFailed {
+    pub path: String,
+    pub expect_status: u16,
+    pub timeout: Duration,
+    /// The last status or error seen
+    pub last: String,
+}
+
+impl std::fmt::Display for SmokeCheckFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Smoke check GET {} did not return {} within {} (last: {})",
+            self.path,
+            self.expect_status,
+            HumanDuration(self.timeout),
+            self.last
+        )
+    }
+}
+
+impl std::error::Error for SmokeCheckFailed {}
+
+/// Poll `check.path` on the app until it returns the expected status,
+/// failing with [`SmokeCheckFailed`] once `check.timeout` has passed.
+/// Returns how long the app took to pass.
+pub async fn smoke_check(
+    client: &RicochetClient,
+    id: &str,
+    check: &SmokeCheck,
+) -> Result<Duration> {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg} [{elapsed}]")?);
+    pb.enable_steady_tick(Duration::from_millis(80));
+    pb.set_message(format!(
+        "Waiting for GET {} to return {}",
+        check.path.bright_cyan(),
+        check.expect_status
+    ));
+
+    let started = Instant::now();
+    loop {
+        let last = match client.check_app(id, &check.path).await {
+            Ok(status) if status.as_u16() == check.expect_status => {
+                pb.finish_and_clear();
+                return Ok(started.elapsed());
+            }
+            Ok(status) => status.to_string(),
+            Err(e) => format!("{e:#}"),
+        };
+
+        if started.elapsed() >= check.timeout {
+            pb.finish_and_clear();
+            return Err(SmokeCheckFailed {
+                path: check.path.clone(),
+                expect_status: check.expect_status,
+                timeout: check.timeout,
+                last,
+            }
+            .into());
+        }
+        pb.set_message(format!(
+            "Waiting for GET {} to return {} (last: {})",
+            check.path.bright_cyan(),
+            check.expect_status,
+            last.dimmed()
+        ));
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -2,14 +2,10 @@ use crate::config::{ServerConfig, parse_server_url};
 use anyhow::{Context, Result};
 use colored::Colorize;
 use reqwest::{Client, Response, StatusCode};
-use ricochet_core::{
-    config::git::{GitCredential, GitProtocol, GitRepo},
-    content::ContentItem,
-};
+use ricochet_core::config::git::{GitCredential, GitProtocol, GitRepo};
 use serde::de::DeserializeOwned;
 use serde_json::json;
 use std::{
-    fs::read_to_string,
     path::Path,
     pin::Pin,
     task::{Context as TaskContext, Poll},
@@ -42,6 +38,13 @@ impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<R> {
     }
 }
 
+fn upload_progress_style() -> indicatif::ProgressStyle {
+    indicatif::ProgressStyle::default_bar()
+        .template("{spinner:.green} {msg} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({percent}%)")
+        .unwrap()
+        .progress_chars("#>-")
+}
+
 pub struct RicochetClient {
     pub(crate) client: Client,
     pub(crate) base_url: Url,
@@ -120,17 +123,26 @@ impl RicochetClient {
         Ok(response.status() == StatusCode::OK)
     }
 
-    /// Fetch the server's RSA public key (PKCS#1 PEM) used to encrypt env vars.
-    pub async fn get_public_key(&self) -> Result<rsa::RsaPublicKey> {
+    /// Fetch the server's RSA public key (PKCS#1 PEM) used to encrypt env vars,
+    /// along with the env var payload versions the server accepts. The key is
+    /// not checked against the pinned fingerprint; see
+    /// [`crate::item::env_vars::pinned_server_key`].
+    pub async fn fetch_public_key(&self) -> Result<crate::crypto::ServerKey> {
         let mut url = self.base_url.clone();
         url.set_path("/api/v0/public-key");
         let response = self.client.get(url).send().await?;
         let status = response.status();
+        let versions = response
+            .headers()
+            .get(crate::crypto::PAYLOAD_VERSIONS_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
         let body = response.text().await?;
         if !status.is_success() {
             anyhow::bail!("Failed to fetch public key (status {status}): {body}");
         }
-        crate::crypto::parse_public_key_pem(&body)
+        let key = crate::crypto::parse_public_key_pem(&body)?;
+        crate::crypto::ServerKey::new(key, versions.as_deref())
     }
 
     /// Check if a key is expired and report if so
@@ -182,17 +194,20 @@ impl RicochetClient {
         }
     }
 
+    /// Bundle the files in `plan` and upload them, with `config` as the
+    /// `_ricochet.toml`. Returns the server's response and the bundle's digest.
     #[allow(clippy::too_many_arguments)]
     pub async fn deploy(
         &self,
         path: &Path,
         content_id: Option<String>,
-        toml_path: &Path,
+        config: String,
+        plan: crate::utils::BundlePlan,
         extra_root_files: &[(std::path::PathBuf, String)],
-        env_vars: Option<crate::crypto::RsaEncryptedEnvVars>,
+        env_vars: Option<crate::crypto::EncryptedEnvVars>,
         pb: &indicatif::ProgressBar,
         debug: bool,
-    ) -> Result<serde_json::Value> {
+    ) -> Result<(serde_json::Value, crate::utils::BundleDigest)> {
         let mut url = self.base_url.clone();
         url.set_path("/api/v0/content/upload");
 
@@ -201,26 +216,57 @@ impl RicochetClient {
             eprintln!("Debug: Base URL: {}", self.base_url);
         }
 
-        let content_item = ContentItem::from_toml(&read_to_string(toml_path)?)?;
-        let include = content_item.content.include;
-        let exclude = content_item.content.exclude;
+        if debug {
+            crate::utils::print_bundle_debug(path, &plan, extra_root_files);
+        }
+
+        // The tarball is compressed and uploaded as it is built, so progress
+        // is measured on the source files read rather than bytes sent
+        pb.set_style(upload_progress_style());
+        pb.set_length(crate::utils::bundle_source_size(
+            &plan.files,
+            extra_root_files,
+        )?);
+        pb.set_position(0);
+        pb.set_message("Bundling and uploading");
+
+        let (reader, digest) = crate::utils::stream_bundle(
+            path.to_path_buf(),
+            plan.files,
+            extra_root_files.to_vec(),
+            Some(pb.clone()),
+        );
+        let bundle_body = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(reader));
+
+        let response = self
+            .upload(bundle_body, content_id, config, env_vars)
+            .await?;
+        let digest = digest.await.context("The bundle was not read to the end")?;
+        Ok((response, digest))
+    }
 
-        // Create a tar bundle from the directory
-        pb.set_message("Creating bundle...");
-        let tar_path =
-            std::env::temp_dir().join(format!("ricochet-{}.tar.gz", ulid::Ulid::generate()));
-        crate::utils::create_bundle(path, &tar_path, include, exclude, extra_root_files, debug)?;
+    /// Upload a gzipped tarball as-is, with `config` as its `_ricochet.toml`.
+    /// Returns the server's response and the bundle's digest.
+    pub async fn upload_bundle(
+        &self,
+        tar_path: &Path,
+        content_id: Option<String>,
+        config: String,
+        env_vars: Option<crate::crypto::EncryptedEnvVars>,
+        pb: &indicatif::ProgressBar,
+    ) -> Result<(serde_json::Value, crate::utils::BundleDigest)> {
+        // Bundles built by other tools may not embed a manifest
+        let digest = crate::utils::BundleDigest {
+            sha256: crate::utils::file_digest(tar_path)?,
+            manifest: crate::utils::read_bundle_file(tar_path, crate::utils::MANIFEST_FILE)?
+                .unwrap_or_default(),
+        };
 
         // Get file size for progress tracking
         let file_size = tokio::fs::metadata(&tar_path).await?.len();
 
         // Change to progress bar with bytes
-        pb.set_style(
-            indicatif::ProgressStyle::default_bar()
-                .template("{spinner:.green} {msg} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({percent}%)")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
+        pb.set_style(upload_progress_style());
         pb.set_length(file_size);
         pb.set_position(0);
         pb.set_message("Uploading to server");
@@ -234,6 +280,22 @@ impl RicochetClient {
         let bundle_body =
             reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(progress_reader));
 
+        let response = self
+            .upload(bundle_body, content_id, config, env_vars)
+            .await?;
+        Ok((response, digest))
+    }
+
+    async fn upload(
+        &self,
+        bundle_body: reqwest::Body,
+        content_id: Option<String>,
+        config: String,
+        env_vars: Option<crate::crypto::EncryptedEnvVars>,
+    ) -> Result<serde_json::Value> {
+        let mut url = self.base_url.clone();
+        url.set_path("/api/v0/content/upload");
+
         let mut form = reqwest::multipart::Form::new().part(
             "bundle",
             reqwest::multipart::Part::stream(bundle_body)
@@ -246,11 +308,9 @@ impl RicochetClient {
             form = form.text("id", id);
         }
         // always include the config file
-        let toml_file = tokio::fs::File::open(toml_path).await?;
-        let toml_body = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(toml_file));
         form = form.part(
             "config",
-            reqwest::multipart::Part::stream(toml_body)
+            reqwest::multipart::Part::text(config)
                 .file_name("_ricochet.toml")
                 .mime_str("application/toml")?,
         );
@@ -363,6 +423,64 @@ impl RicochetClient {
         Self::handle_response(resp).await
     }
 
+    /// Fetch a task's schedule
+    pub async fn get_schedule(&self, id: &str) -> Result<crate::item::schedule::Schedule> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!("/api/v0/content/{id}/schedule"));
+
+        let resp = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        Self::handle_response(resp).await
+    }
+
+    /// Remove a task's schedule
+    pub async fn clear_schedule(&self, id: &str) -> Result<()> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!("/api/v0/content/{id}/schedule"));
+
+        let response = self
+            .client
+            .delete(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Failed to clear schedule: {}", error_text)
+        }
+
+        Ok(())
+    }
+
+    /// Pause or resume a task's schedule, keeping its cron expression
+    pub async fn set_schedule_paused(
+        &self,
+        id: &str,
+        paused: bool,
+    ) -> Result<crate::item::schedule::Schedule> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!("/api/v0/content/{id}/schedule"));
+
+        let resp = self
+            .client
+            .patch(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .json(&json!({"paused": paused}))
+            .send()
+            .await?;
+
+        Self::handle_response(resp).await
+    }
+
     pub async fn stop_invocation(&self, id: &str, invocation_id: &str) -> Result<()> {
         let mut url = self.base_url.clone();
         url.set_path(&format!(
@@ -388,6 +506,68 @@ impl RicochetClient {
         Ok(())
     }
 
+    /// List past and running invocations of a task.
+    pub async fn list_invocations(
+        &self,
+        id: &str,
+    ) -> Result<Vec<crate::item::invocation::InvocationRow>> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!("/api/v0/content/{}/invocations", id));
+
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Fetch the current state of a task invocation.
+    pub async fn get_invocation(&self, id: &str, invocation_id: &str) -> Result<serde_json::Value> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "/api/v0/content/{}/invocations/{}",
+            id, invocation_id
+        ));
+
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Fetch the combined stdout and stderr of a task invocation as plain text.
+    pub async fn get_invocation_logs(&self, id: &str, invocation_id: &str) -> Result<String> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "/api/v0/content/{}/invocations/{}/logs",
+            id, invocation_id
+        ));
+
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Failed to fetch invocation logs: {}", error_text)
+        }
+
+        Ok(response.text().await?)
+    }
+
     pub async fn list_instances(&self, id: &str) -> Result<serde_json::Value> {
         let mut url = self.base_url.clone();
         url.set_path(&format!("/api/v0/content/{}/instances", id));
@@ -424,6 +604,49 @@ impl RicochetClient {
         Ok(())
     }
 
+    /// Fetch the lines an instance has written, oldest first. With `since`,
+    /// only lines written at or after it are returned.
+    pub async fn get_instance_logs(
+        &self,
+        id: &str,
+        pid: &str,
+        since: Option<jiff::Timestamp>,
+    ) -> Result<Vec<crate::app::logs::LogLine>> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!("/api/v0/content/{}/instances/{}/logs", id, pid));
+        if let Some(since) = since {
+            url.query_pairs_mut()
+                .append_pair("since", &since.to_string());
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        Self::handle_response(response).await
+    }
+
+    /// Request `path` from a deployed app and return the response status.
+    /// Redirects are not followed, so the status is the app's own answer.
+    pub async fn check_app(&self, id: &str, path: &str) -> Result<StatusCode> {
+        let url = crate::app::app_url(&self.base_url, id)?.join(path.trim_start_matches('/'))?;
+
+        let client = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let response = client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?;
+
+        Ok(response.status())
+    }
+
     pub async fn delete(&self, id: &str) -> Result<()> {
         let mut url = self.base_url.clone();
         url.set_path(&format!("/api/v0/content/{}", id));
@@ -499,6 +722,96 @@ impl RicochetClient {
         Self::handle_response(response).await
     }
 
+    /// Redeploy an earlier deployment of a content item. Returns the new
+    /// deployment, which builds like any other.
+    pub async fn rollback(
+        &self,
+        content_ulid: &str,
+        deployment_ulid: &str,
+    ) -> Result<crate::item::deployment::DeploymentRow> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "/api/v0/content/{}/deployments/{}/rollback",
+            content_ulid, deployment_ulid
+        ));
+        let response = self
+            .client
+            .post(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+        Self::handle_response(response).await
+    }
+
+    /// Fetch the build log of a deployment (renv restore, uv sync, etc.) as plain text.
+    pub async fn get_deployment_logs(&self, deployment_ulid: &str) -> Result<String> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "/api/v0/content/deployments/{}/logs",
+            deployment_ulid
+        ));
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Failed to fetch deployment logs: {}", error_text)
+        }
+
+        Ok(response.text().await?)
+    }
+
+    /// Fetch a deployment's build log from byte `offset` onward, asking for
+    /// just that range. Returns where the bytes start in the log: `offset`,
+    /// or 0 if the server sent a log shorter than `offset`, i.e. it was
+    /// rewritten. Servers that ignore `Range` send the whole log, which is
+    /// trimmed here.
+    pub async fn get_deployment_logs_from(
+        &self,
+        deployment_ulid: &str,
+        offset: usize,
+    ) -> Result<(usize, Vec<u8>)> {
+        let mut url = self.base_url.clone();
+        url.set_path(&format!(
+            "/api/v0/content/deployments/{}/logs",
+            deployment_ulid
+        ));
+        let response = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Key {}", self.api_key))
+            .header(reqwest::header::RANGE, format!("bytes={offset}-"))
+            .send()
+            .await?;
+
+        match response.status() {
+            // Nothing past `offset` yet
+            StatusCode::RANGE_NOT_SATISFIABLE => Ok((offset, Vec::new())),
+            StatusCode::PARTIAL_CONTENT => Ok((offset, response.bytes().await?.to_vec())),
+            status if status.is_success() => {
+                let log = response.bytes().await?;
+                match log.get(offset..) {
+                    Some(new) => Ok((offset, new.to_vec())),
+                    None => Ok((0, log.to_vec())),
+                }
+            }
+            _ => {
+                let error_text = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "Unknown error".to_string());
+                anyhow::bail!("Failed to fetch deployment logs: {}", error_text)
+            }
+        }
+    }
+
     /// List the names of an item's environment variables.
     pub async fn get_env_vars(&self, id: &str) -> Result<Vec<String>> {
         let mut url = self.base_url.clone();
@@ -531,7 +844,7 @@ impl RicochetClient {
     pub async fn upsert_env_vars(
         &self,
         id: &str,
-        encrypted: &crate::crypto::RsaEncryptedEnvVars,
+        encrypted: &crate::crypto::EncryptedEnvVars,
     ) -> Result<Vec<String>> {
         let mut url = self.base_url.clone();
         url.set_path(&format!("/api/v0/content/{}/env-vars", id));
@@ -550,7 +863,7 @@ impl RicochetClient {
     pub async fn replace_env_vars(
         &self,
         id: &str,
-        encrypted: &crate::crypto::RsaEncryptedEnvVars,
+        encrypted: &crate::crypto::EncryptedEnvVars,
     ) -> Result<Vec<String>> {
         let mut url = self.base_url.clone();
         url.set_path(&format!("/api/v0/content/{}/env-vars", id));
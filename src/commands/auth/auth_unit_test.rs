@@ -254,6 +254,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("https://prod.ricochet.com").unwrap(),
                 api_key: Some("rico_prod_key".to_string()),
+                key_fingerprint: None,
             },
         );
         servers.insert(
@@ -261,6 +262,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("https://staging.ricochet.com").unwrap(),
                 api_key: Some("rico_staging_key".to_string()),
+                key_fingerprint: None,
             },
         );
         servers.insert(
@@ -268,6 +270,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("http://localhost:3000").unwrap(),
                 api_key: None,
+                key_fingerprint: None,
             },
         );
         Config {
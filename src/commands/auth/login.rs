@@ -655,6 +655,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("https://ricochet.example.com").expect("valid URL"),
                 api_key: None,
+                key_fingerprint: None,
             },
         );
         let config = Config {
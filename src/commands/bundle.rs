@@ -0,0 +1,90 @@
+use anyhow::{Result, bail};
+use colored::Colorize;
+use ricochet_core::content::ContentItem;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Build the deployment bundle for `path` and write it to `output`, ready to
+/// upload later with `ricochet deploy --bundle`.
+pub async fn bundle(path: &Path, output: &Path, use_gitignore: bool, debug: bool) -> Result<()> {
+    if !path.is_dir() {
+        bail!("Path must be a directory containing _ricochet.toml");
+    }
+
+    let toml_path = path.join("_ricochet.toml");
+    if !toml_path.exists() {
+        bail!(
+            "No _ricochet.toml found in {}. Please create one with `ricochet init`",
+            path.display()
+        );
+    }
+
+    let ricochet_toml = ContentItem::from_toml(&std::fs::read_to_string(&toml_path)?)?;
+    let include = ricochet_toml.content.include;
+    let exclude = ricochet_toml.content.exclude;
+
+    let mut extra_root_files =
+        crate::commands::deploy::resolve_extra_root_files(path, &ricochet_toml.language.packages)?;
+    for (found, _) in &extra_root_files {
+        println!(
+            "  {} Using {} from workspace root",
+            "→".bright_cyan(),
+            found.display().to_string().bright_cyan()
+        );
+    }
+
+    // `deploy --bundle` reads the configuration from the bundle root, so embed
+    // it even when `include` leaves it out
+    let plan = crate::utils::plan_bundle(path, include.clone(), exclude.clone(), use_gitignore)?;
+    if !plan.files.contains(&toml_path) {
+        extra_root_files.push((toml_path, "_ricochet.toml".to_string()));
+    }
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let build = tokio::task::spawn_blocking({
+        let (path, output, cancel) = (path.to_path_buf(), output.to_path_buf(), cancel.clone());
+        move || {
+            crate::utils::create_bundle(
+                &path,
+                &output,
+                include,
+                exclude,
+                &extra_root_files,
+                use_gitignore,
+                debug,
+                Some(&cancel),
+            )
+        }
+    });
+    tokio::pin!(build);
+
+    // Exiting on Ctrl-C skips destructors, so stop the build and let it
+    // remove its temp file before returning. A second Ctrl-C exits at once.
+    let digest = tokio::select! {
+        result = &mut build => result,
+        _ = tokio::signal::ctrl_c() => {
+            crate::utils::exit_on_ctrl_c();
+            cancel.store(true, Ordering::Relaxed);
+            let _ = build.await;
+            bail!("Bundling cancelled");
+        }
+    };
+    crate::utils::exit_on_ctrl_c();
+    let digest = digest??;
+
+    let size = std::fs::metadata(output)?.len();
+    println!(
+        "{} Wrote {} ({})",
+        "✓".green().bold(),
+        output.display().to_string().bright_cyan(),
+        crate::utils::format_size(size)
+    );
+    println!("  Digest: {}", format!("sha256:{digest}").dimmed());
+    println!(
+        "  Deploy it with {}",
+        format!("ricochet deploy --bundle {}", output.display()).bright_cyan()
+    );
+
+    Ok(())
+}
@@ -51,6 +51,11 @@ pub fn show(config: &Config, show_all: bool) -> Result<()> {
             } else {
                 println!("    API Key: {}", "Not configured".yellow());
             }
+
+            match &server_config.key_fingerprint {
+                Some(fingerprint) => println!("    Key fingerprint: {}", fingerprint),
+                None => println!("    Key fingerprint: {}", "Not pinned".dimmed()),
+            }
         }
     }
 
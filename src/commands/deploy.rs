@@ -1,21 +1,64 @@
-use crate::{client::RicochetClient, config::Config};
-use anyhow::{Result, bail};
+use crate::{
+    OutputFormat,
+    client::RicochetClient,
+    config::{Config, ServerConfig},
+    utils::{ManifestDiff, SkipReason},
+};
+use anyhow::{Context, Result, bail};
 use colored::Colorize;
 use dialoguer::{Confirm, theme::ColorfulTheme};
-use indicatif::{ProgressBar, ProgressStyle};
-use ricochet_core::{config::git::GitRepo, content::ContentItem, language::Package};
-use std::path::PathBuf;
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
+use ricochet_core::{
+    config::git::GitRepo, content::ContentItem, events::DeploymentStatus, language::Package,
+};
+use rsa::sha2::{Digest, Sha256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Options for [`deploy`] beyond the target server and content path.
+#[derive(Debug, Default)]
+pub struct DeployOptions {
+    /// Name for the deployment (not yet sent to the server)
+    pub name: Option<String>,
+    /// Description for the deployment (not yet sent to the server)
+    pub description: Option<String>,
+    /// `KEY[=VALUE]` entries to set as environment variables on a first deployment
+    pub env: Vec<String>,
+    /// Dotenv files whose every key is set on a first deployment
+    pub env_files: crate::env_vars::EnvFiles,
+    /// Send the variables from `env_files` without asking
+    pub yes: bool,
+    /// Encrypt environment variables for a server that isn't configured, whose
+    /// key can't be checked against a pin
+    pub trust_unpinned_key: bool,
+    /// Wait for the deployment to finish and fail unless it succeeds
+    pub wait: bool,
+    /// Stream the build log while waiting for the deployment to finish
+    pub logs: bool,
+    /// Upload this pre-built bundle instead of bundling the content directory
+    pub bundle: Option<PathBuf>,
+    /// Also skip files matched by `.gitignore` files when bundling
+    pub gitignore: bool,
+    /// Upload even if the last recorded deployment had the same files and config
+    pub force: bool,
+    /// Show which files changed since the last recorded deployment first
+    pub diff: bool,
+    /// Once the deployment succeeds, wait for the app to pass this check
+    pub smoke: Option<crate::app::smoke::SmokeCheck>,
+    /// Roll back to the previous successful deployment if the smoke check fails
+    pub rollback_on_failure: bool,
+    /// Print debug output, including the list of bundled files
+    pub debug: bool,
+}
 
 pub async fn deploy(
     config: &Config,
     server_ref: Option<&str>,
     path: PathBuf,
-    _name: Option<String>,
-    _description: Option<String>,
-    env: Vec<String>,
-    debug: bool,
+    opts: DeployOptions,
 ) -> Result<()> {
-    if !path.exists() {
+    if opts.bundle.is_none() && !path.exists() {
         anyhow::bail!("Path does not exist: {}", path.display());
     }
 
@@ -26,96 +69,119 @@ pub async fn deploy(
 
     client.preflight_key_check().await?;
 
-    // Check for _ricochet.toml
-    let toml_path = if path.is_dir() {
-        path.join("_ricochet.toml")
-    } else {
-        anyhow::bail!("Path must be a directory containing _ricochet.toml");
-    };
-
-    if !toml_path.exists() {
-        // Check if we're in an interactive terminal (not in tests or CI)
-        if !crate::utils::is_non_interactive() {
-            let confirmed = Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt(format!(
-                    "No _ricochet.toml found. Would you like to create one? (deploying to {})",
-                    server_config.url.as_str().trim_end_matches('/')
-                ))
-                .default(true)
-                .interact()?;
-
-            if !confirmed {
-                anyhow::bail!("No _ricochet.toml provided. Please create one with `ricochet init`");
-            }
-
-            // Create _ricochet.toml using init command
-            crate::commands::init::init_rico_toml(&path, false, false)?;
-        } else {
-            // Non-interactive mode (tests, CI, etc.)
-            anyhow::bail!(
-                "No _ricochet.toml found in {}. Please create one with `ricochet init`",
-                path.display()
-            );
+    // A pre-built bundle carries its own _ricochet.toml; otherwise read it from `path`
+    let (toml_content, toml_path) = match &opts.bundle {
+        Some(bundle) => (crate::utils::read_bundle_config(bundle)?, None),
+        None => {
+            let toml_path = ensure_ricochet_toml(&path, &server_config)?;
+            (std::fs::read_to_string(&toml_path)?, Some(toml_path))
         }
-    }
-
-    // Read and parse _ricochet.toml
-    let toml_content = std::fs::read_to_string(&toml_path)?;
+    };
     let ricochet_toml = ContentItem::from_toml(&toml_content)?;
 
     let content_id = ricochet_toml.content.id.clone();
     let content_type = ricochet_toml.content.content_type;
 
-    // check for existence of packages file, searching parent dirs for uv workspaces
-    let pkgs = ricochet_toml.language.packages;
-    let pkg_path = path.join(pkgs.to_string());
-    let mut extra_root_files = Vec::new();
+    // A pre-built bundle already contains everything it needs
+    let extra_root_files = match opts.bundle {
+        Some(_) => Vec::new(),
+        None => resolve_extra_root_files(&path, &ricochet_toml.language.packages)?,
+    };
+    for (found, _) in &extra_root_files {
+        println!(
+            "  {} Using {} from workspace root",
+            "→".bright_cyan(),
+            found.display().to_string().bright_cyan()
+        );
+    }
 
-    if !pkg_path.exists() {
-        if let Package::UvLock = pkgs {
-            // uv workspaces keep uv.lock at the workspace root — search parent dirs
-            if let Some(found) = crate::utils::find_in_parent_dirs(&path, "uv.lock") {
-                println!(
-                    "  {} Using {} from workspace root",
-                    "→".bright_cyan(),
-                    found.display().to_string().bright_cyan()
-                );
-                extra_root_files.push((found, "uv.lock".to_string()));
-            } else {
-                bail!(
-                    "Required package file `uv.lock` not found.\n  {} Create it by running `uv init`",
-                    "Hint:".yellow().bold(),
-                );
-            }
-        } else {
-            let hint = match pkgs {
-                Package::RenvLock => "Create it by running `renv::snapshot()` in R",
-                Package::ManifestToml => "Create it by running `Pkg.instantiate()` in Julia",
-                Package::UvLock => unreachable!(),
-            };
-            bail!(
-                "Required package file `{}` not found.\n  {} {}",
-                pkgs,
-                "Hint:".yellow().bold(),
-                hint
+    // Walk the content directory once; the up-to-date check, --diff and the
+    // upload all work from this plan
+    let plan = match opts.bundle {
+        Some(_) => None,
+        None => Some(crate::utils::plan_bundle(
+            &path,
+            ricochet_toml.content.include.clone(),
+            ricochet_toml.content.exclude.clone(),
+            opts.gitignore,
+        )?),
+    };
+
+    // CI pipelines redeploy on every merge; skip the upload (and the rebuild
+    // it triggers) when these exact files and config were deployed last time
+    let config_digest = format!("{:x}", Sha256::digest(toml_content.as_bytes()));
+    let server = server_config.url.as_str().trim_end_matches('/');
+    // The state lives next to _ricochet.toml, so a pre-built bundle, which may
+    // be deployed from any directory, is neither skipped nor recorded
+    let last = match (&opts.bundle, &content_id) {
+        (None, Some(id)) => last_deployment(&path, server, id),
+        _ => None,
+    };
+    let check_unchanged =
+        !opts.force && opts.env.is_empty() && opts.env_files.is_empty() && last.is_some();
+
+    // Hash each file at most once, and only if something compares the result
+    let manifest = match (&opts.bundle, &plan) {
+        _ if !check_unchanged && !opts.diff => None,
+        (Some(bundle), _) => Some(crate::utils::parse_manifest(
+            &crate::utils::read_bundle_file(bundle, crate::utils::MANIFEST_FILE)?
+                .unwrap_or_default(),
+        )),
+        (None, Some(plan)) => Some(crate::utils::bundle_manifest(
+            &path,
+            &plan.files,
+            &extra_root_files,
+        )?),
+        (None, None) => None,
+    };
+
+    if check_unchanged
+        && let Some(id) = &content_id
+        && let Some(last) = &last
+    {
+        let unchanged = manifest.as_ref().is_some_and(|current| {
+            load_manifest(&path, &last.digest).is_some_and(|previous| &previous == current)
+        });
+        if unchanged
+            && last.config_digest.as_deref() == Some(config_digest.as_str())
+            && succeeded(&client, last).await
+        {
+            println!(
+                "{} {} is up to date ({}), nothing to deploy",
+                "✓".green().bold(),
+                id.bright_cyan(),
+                last.digest.dimmed()
             );
+            println!("  Use {} to deploy it again", "--force".bright_cyan());
+            return Ok(());
         }
     }
 
-    // if python and no .python-version, check parent dirs (workspace root)
-    if let Package::UvLock = pkgs
-        && !path.join(".python-version").exists()
+    if opts.diff
+        && let Some(current) = &manifest
     {
-        if let Some(found) = crate::utils::find_in_parent_dirs(&path, ".python-version") {
+        let previous = last
+            .as_ref()
+            .and_then(|record| load_manifest(&path, &record.digest));
+        if previous.is_none() {
             println!(
-                "  {} Using {} from workspace root",
-                "→".bright_cyan(),
-                found.display().to_string().bright_cyan()
+                "{}",
+                "No manifest recorded for an earlier deployment from here; every file is new."
+                    .dimmed()
             );
-            extra_root_files.push((found, ".python-version".to_string()));
-        } else {
-            bail!("Please create a `.python-version` via `uv python pin`")
         }
+        let diff = crate::utils::diff_manifests(&previous.unwrap_or_default(), current);
+        print_manifest_diff(&diff);
+
+        if !crate::utils::is_non_interactive()
+            && !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Deploy these changes?")
+                .default(true)
+                .interact()?
+        {
+            bail!("Deployment cancelled");
+        }
+        println!();
     }
 
     if let Some(ref id) = content_id {
@@ -131,13 +197,24 @@ pub async fn deploy(
     }
 
     // Resolve and encrypt environment variables, if any were provided.
-    // Only named keys are sent; whole dotfiles are never auto-loaded.
-    let env_vars = if env.is_empty() {
+    // Only named keys are sent; whole dotfiles are never auto-loaded unless
+    // passed with --from-file.
+    let env_vars = if opts.env.is_empty() && opts.env_files.is_empty() {
         None
     } else {
-        let resolved = crate::env_vars::resolve_env_vars(&env, &path)?;
-        let pub_key = client.get_public_key().await?;
-        Some(crate::crypto::encrypt_env_vars(&pub_key, &resolved)?)
+        let resolved = crate::env_vars::resolve_with_files(&opts.env, &opts.env_files, &path)?;
+        if !opts.env_files.is_empty() {
+            crate::item::env_vars::print_pending_names(&resolved);
+            if !opts.yes
+                && !crate::utils::confirm("Send these environment variables?")
+                    .context("Pass --yes to send them without asking")?
+            {
+                bail!("Deployment cancelled");
+            }
+        }
+        let server_key =
+            crate::item::env_vars::pinned_server_key(&client, opts.trust_unpinned_key).await?;
+        Some(crate::crypto::encrypt_for_server(&server_key, &resolved)?)
     };
 
     let pb = ProgressBar::new_spinner();
@@ -148,28 +225,76 @@ pub async fn deploy(
     );
     pb.enable_steady_tick(std::time::Duration::from_millis(80));
 
-    match client
-        .deploy(
-            &path,
-            content_id.clone(),
-            &toml_path,
-            &extra_root_files,
-            env_vars,
-            &pb,
-            debug,
-        )
-        .await
-    {
-        Ok(response) => {
+    let upload = async {
+        match (&opts.bundle, plan) {
+            (Some(bundle), _) => {
+                client
+                    .upload_bundle(bundle, content_id.clone(), toml_content, env_vars, &pb)
+                    .await
+            }
+            (None, Some(plan)) => {
+                client
+                    .deploy(
+                        &path,
+                        content_id.clone(),
+                        toml_content,
+                        plan,
+                        &extra_root_files,
+                        env_vars,
+                        &pb,
+                        opts.debug,
+                    )
+                    .await
+            }
+            (None, None) => {
+                unreachable!("the bundle is planned when not deploying a pre-built one")
+            }
+        }
+    };
+
+    // The bundle is streamed straight into the request, so abandoning the
+    // upload on Ctrl-C stops the bundling thread and leaves nothing on disk
+    let result = tokio::select! {
+        result = upload => result,
+        _ = tokio::signal::ctrl_c() => {
+            pb.finish_and_clear();
+            bail!("Deployment cancelled");
+        }
+    };
+    // Waiting, the smoke check and a rollback can take minutes; keep Ctrl-C
+    // working for them
+    crate::utils::exit_on_ctrl_c();
+
+    match result {
+        Ok((response, digest)) => {
             pb.finish_and_clear();
 
+            let deployment_id = response
+                .get("deployment_id")
+                .or_else(|| response.get("deploymentId"))
+                .and_then(|v| v.as_str());
+            // With --wait the upload is only the first step; report the build outcome below
+            let wait = opts.wait || opts.logs || opts.smoke.is_some();
+            let uploaded = if wait {
+                "Bundle uploaded"
+            } else {
+                "Deployment successful!"
+            };
+            let mut record = None;
+
             if let Some(id) = response.get("id").and_then(|v| v.as_str()) {
-                println!("{} Deployment successful!", "✓".green().bold());
+                println!("{} {}", "✓".green().bold(), uploaded);
+                println!(
+                    "  Bundle digest: {}",
+                    format!("sha256:{}", digest.sha256).dimmed()
+                );
 
                 // Update _ricochet.toml with the content ID if it's a new deployment
-                if content_id.is_none() {
+                if content_id.is_none()
+                    && let Some(toml_path) = &toml_path
+                {
                     // Read the original file content
-                    let original_content = std::fs::read_to_string(&toml_path)?;
+                    let original_content = std::fs::read_to_string(toml_path)?;
 
                     // Find the [content] section and add/update the id field
                     let updated_content = if original_content.contains("id =") {
@@ -187,7 +312,13 @@ pub async fn deploy(
                             .to_string()
                     };
 
-                    std::fs::write(&toml_path, updated_content)?;
+                    std::fs::write(toml_path, updated_content)?;
+                } else if content_id.is_none() {
+                    eprintln!(
+                        "{} The bundle's _ricochet.toml has no id, so deploying it again creates another content item.\n  Add {} under [content] and rebuild the bundle to update this one instead.",
+                        "⚠".yellow(),
+                        format!("id = \"{}\"", id).bright_cyan()
+                    );
                 }
 
                 // Get server URL and construct links
@@ -196,21 +327,101 @@ pub async fn deploy(
                 println!("\n{}", "Links:".bold());
 
                 // Show deployment link if deployment_id is available
-                if let Some(deployment_id) = response
-                    .get("deployment_id")
-                    .or_else(|| response.get("deploymentId"))
-                    .and_then(|v| v.as_str())
-                {
+                if let Some(deployment_id) = deployment_id {
                     println!("  Deployment: {}/deployments/{}", base_url, deployment_id);
                 }
 
                 // Show app overview link
                 println!("  App Overview: {}/apps/{}/overview", base_url, id);
+
+                record = Some(DeploymentRecord {
+                    content_id: id.to_string(),
+                    deployment_id: deployment_id.map(str::to_string),
+                    server: base_url.to_string(),
+                    digest: format!("sha256:{}", digest.sha256),
+                    config_digest: Some(config_digest),
+                    status: None,
+                    deployed_at: chrono::Utc::now(),
+                });
             } else {
-                println!("{} Deployment successful!", "✓".green().bold());
+                println!("{} {}", "✓".green().bold(), uploaded);
+                println!(
+                    "  Bundle digest: {}",
+                    format!("sha256:{}", digest.sha256).dimmed()
+                );
                 println!("\n{}", serde_json::to_string_pretty(&response)?);
             }
 
+            if wait {
+                let Some(deployment_id) = deployment_id else {
+                    bail!(
+                        "The server did not return a deployment ID, so there is nothing to wait for"
+                    );
+                };
+                println!();
+                let started = std::time::Instant::now();
+                let finished = if opts.logs {
+                    crate::item::deployment::follow_deployment_logs(&client, deployment_id).await?
+                } else {
+                    crate::item::deployment::wait_for_deployment(&client, deployment_id).await?
+                };
+                crate::item::deployment::report_outcome(&finished, started.elapsed())?;
+                if let Some(record) = &mut record {
+                    record.status = Some(finished.status);
+                }
+            }
+
+            if let Some(check) = &opts.smoke {
+                let Some(id) = response
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .or(content_id.as_deref())
+                else {
+                    bail!("The server did not return a content ID, so there is no app to check");
+                };
+                match crate::app::smoke::smoke_check(&client, id, check).await {
+                    Ok(elapsed) => println!(
+                        "{} Smoke check passed: GET {} returned {} after {}",
+                        "✓".green().bold(),
+                        check.path.bright_cyan(),
+                        check.expect_status,
+                        HumanDuration(elapsed)
+                    ),
+                    Err(e) => {
+                        eprintln!("{} {}", "✗".red().bold(), e);
+                        if opts.rollback_on_failure {
+                            eprintln!(
+                                "{} Rolling back to the previous successful deployment",
+                                "→".bright_cyan()
+                            );
+                            if let Err(rollback) = crate::item::deployment::rollback(
+                                config, server_ref, id, None, true, true,
+                            )
+                            .await
+                            {
+                                eprintln!("{} Rollback failed: {:#}", "✗".red().bold(), rollback);
+                            }
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+
+            // Keep a local record of what was uploaded, unless it or its
+            // smoke check is known to have failed; the upload itself went
+            // through, so only warn if the record can't be written
+            if let Some(record) = &record
+                && opts.bundle.is_none()
+                && path.is_dir()
+                && let Err(e) = record_deployment(&path, record, &digest.manifest)
+            {
+                eprintln!(
+                    "{} Could not record the deployment locally: {:#}",
+                    "⚠".yellow(),
+                    e
+                );
+            }
+
             Ok(())
         }
         Err(e) => {
@@ -263,6 +474,179 @@ pub async fn deploy(
     }
 }
 
+/// Where `deploy` keeps its local state, relative to the content directory.
+pub const DEPLOYMENTS_LOG: &str = ".ricochet/deployments.jsonl";
+
+/// A line of the local deployment state: the last bundle uploaded for one
+/// content item on one server.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeploymentRecord {
+    pub content_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment_id: Option<String>,
+    /// Base URL of the server deployed to
+    pub server: String,
+    /// `sha256:` digest of the uploaded bundle
+    pub digest: String,
+    /// SHA-256 of the `_ricochet.toml` sent alongside the bundle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_digest: Option<String>,
+    /// Final status of the deployment, if `deploy` waited for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<DeploymentStatus>,
+    pub deployed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Where `deploy` keeps the manifest of each bundle it uploaded, named by
+/// the bundle's SHA-256, relative to the content directory.
+pub const MANIFESTS_DIR: &str = ".ricochet/manifests";
+
+/// Record `record` in `dir` as the latest deployment of its content item to
+/// its server, replacing the earlier one, and keep the uploaded bundle's
+/// `manifest` for later `--diff`s. Manifests no record refers to any more
+/// are removed, so the state holds one entry per server and content item.
+fn record_deployment(dir: &Path, record: &DeploymentRecord, manifest: &str) -> Result<()> {
+    let log_path = dir.join(DEPLOYMENTS_LOG);
+    let others: Vec<DeploymentRecord> = std::fs::read_to_string(&log_path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<DeploymentRecord>(line).ok())
+        .filter(|r| r.server != record.server || r.content_id != record.content_id)
+        .collect();
+
+    let manifests = dir.join(MANIFESTS_DIR);
+    if !manifest.is_empty() {
+        std::fs::create_dir_all(&manifests)?;
+        let name = record.digest.trim_start_matches("sha256:");
+        std::fs::write(manifests.join(name), manifest)?;
+    }
+
+    let mut log = String::new();
+    for r in others.iter().chain([record]) {
+        log.push_str(&serde_json::to_string(r)?);
+        log.push('\n');
+    }
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&log_path, log)
+        .with_context(|| format!("Failed to write {}", log_path.display()))?;
+
+    let kept: Vec<&str> = others
+        .iter()
+        .chain([record])
+        .map(|r| r.digest.trim_start_matches("sha256:"))
+        .collect();
+    if let Ok(entries) = std::fs::read_dir(&manifests) {
+        for entry in entries.flatten() {
+            if !kept.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The most recent deployment of `content_id` to `server` recorded in `dir`,
+/// ignoring any that are known not to have succeeded.
+fn last_deployment(dir: &Path, server: &str, content_id: &str) -> Option<DeploymentRecord> {
+    let log = std::fs::read_to_string(dir.join(DEPLOYMENTS_LOG)).ok()?;
+    log.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<DeploymentRecord>(line).ok())
+        .filter(|record| {
+            record
+                .status
+                .as_ref()
+                .is_none_or(|status| matches!(status, DeploymentStatus::Success))
+        })
+        .find(|record| record.server == server && record.content_id == content_id)
+}
+
+/// Whether the deployment in `record` succeeded, asking the server when
+/// `deploy` didn't wait to find out.
+async fn succeeded(client: &RicochetClient, record: &DeploymentRecord) -> bool {
+    match (&record.status, &record.deployment_id) {
+        (Some(status), _) => matches!(status, DeploymentStatus::Success),
+        (None, Some(id)) => client
+            .get_deployment(id)
+            .await
+            .is_ok_and(|d| matches!(d.status, DeploymentStatus::Success)),
+        (None, None) => false,
+    }
+}
+
+/// The manifest recorded in `dir` for the bundle with `digest`, if any.
+fn load_manifest(dir: &Path, digest: &str) -> Option<BTreeMap<String, String>> {
+    let name = digest.trim_start_matches("sha256:");
+    let manifest = std::fs::read_to_string(dir.join(MANIFESTS_DIR).join(name)).ok()?;
+    Some(crate::utils::parse_manifest(&manifest))
+}
+
+/// Print a `git status`-style summary of `diff`.
+fn print_manifest_diff(diff: &ManifestDiff) {
+    if diff.is_empty() {
+        println!("No file changes since the last deployment");
+        return;
+    }
+
+    let modified = diff.modified.len();
+    println!(
+        "{} {} modified, {} added, {} removed",
+        modified,
+        if modified == 1 { "file" } else { "files" },
+        diff.added.len(),
+        diff.removed.len()
+    );
+    for path in &diff.modified {
+        println!("  {} {}", "M".yellow().bold(), path);
+    }
+    for path in &diff.added {
+        println!("  {} {}", "A".green().bold(), path);
+    }
+    for path in &diff.removed {
+        println!("  {} {}", "D".red().bold(), path);
+    }
+}
+
+/// Return the path of `_ricochet.toml` in `path`, offering to create it when
+/// running interactively.
+fn ensure_ricochet_toml(path: &Path, server_config: &ServerConfig) -> Result<PathBuf> {
+    let toml_path = if path.is_dir() {
+        path.join("_ricochet.toml")
+    } else {
+        anyhow::bail!("Path must be a directory containing _ricochet.toml");
+    };
+
+    if !toml_path.exists() {
+        // Check if we're in an interactive terminal (not in tests or CI)
+        if !crate::utils::is_non_interactive() {
+            let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "No _ricochet.toml found. Would you like to create one? (deploying to {})",
+                    server_config.url.as_str().trim_end_matches('/')
+                ))
+                .default(true)
+                .interact()?;
+
+            if !confirmed {
+                anyhow::bail!("No _ricochet.toml provided. Please create one with `ricochet init`");
+            }
+
+            // Create _ricochet.toml using init command
+            crate::commands::init::init_rico_toml(&path.to_path_buf(), false, false)?;
+        } else {
+            // Non-interactive mode (tests, CI, etc.)
+            anyhow::bail!(
+                "No _ricochet.toml found in {}. Please create one with `ricochet init`",
+                path.display()
+            );
+        }
+    }
+
+    Ok(toml_path)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn deploy_git(
     config: &Config,
@@ -320,3 +704,219 @@ pub async fn deploy_git(
         }
     }
 }
+
+/// Check that the package file for `pkgs` exists in `path` and return any
+/// files to add at the bundle root from a parent directory. uv workspaces keep
+/// `uv.lock` and `.python-version` at the workspace root.
+pub(crate) fn resolve_extra_root_files(
+    path: &Path,
+    pkgs: &Package,
+) -> Result<Vec<(PathBuf, String)>> {
+    let mut extra_root_files = Vec::new();
+
+    if !path.join(pkgs.to_string()).exists() {
+        if let Package::UvLock = pkgs {
+            if let Some(found) = crate::utils::find_in_parent_dirs(path, "uv.lock") {
+                extra_root_files.push((found, "uv.lock".to_string()));
+            } else {
+                bail!(
+                    "Required package file `uv.lock` not found.\n  {} Create it by running `uv init`",
+                    "Hint:".yellow().bold(),
+                );
+            }
+        } else {
+            let hint = match pkgs {
+                Package::RenvLock => "Create it by running `renv::snapshot()` in R",
+                Package::ManifestToml => "Create it by running `Pkg.instantiate()` in Julia",
+                Package::UvLock => unreachable!(),
+            };
+            bail!(
+                "Required package file `{}` not found.\n  {} {}",
+                pkgs,
+                "Hint:".yellow().bold(),
+                hint
+            );
+        }
+    }
+
+    // if python and no .python-version, check parent dirs (workspace root)
+    if let Package::UvLock = pkgs
+        && !path.join(".python-version").exists()
+    {
+        if let Some(found) = crate::utils::find_in_parent_dirs(path, ".python-version") {
+            extra_root_files.push((found, ".python-version".to_string()));
+        } else {
+            bail!("Please create a `.python-version` via `uv python pin`")
+        }
+    }
+
+    Ok(extra_root_files)
+}
+
+/// A file that would be uploaded by `deploy`.
+#[derive(Debug, Serialize)]
+pub struct BundleFile {
+    /// Path inside the bundle
+    pub path: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Where the file comes from, if outside the content directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// A path left out of the bundle and the rule responsible.
+#[derive(Debug, Serialize)]
+pub struct SkippedPath {
+    pub path: String,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+/// What `deploy --dry-run` reports about a content directory.
+#[derive(Debug, Serialize)]
+pub struct BundleReport {
+    pub files: Vec<BundleFile>,
+    pub skipped: Vec<SkippedPath>,
+    /// Sum of the uncompressed file sizes in bytes
+    pub total_size: u64,
+    /// Size of the gzipped tarball in bytes
+    pub compressed_size: u64,
+    /// `sha256:` digest of the gzipped tarball, as recorded after a deploy
+    pub digest: String,
+}
+
+/// Resolve `_ricochet.toml` in `path` and work out exactly what `deploy`
+/// would upload, without contacting the server.
+pub fn plan_deploy_bundle(path: &Path, use_gitignore: bool) -> Result<BundleReport> {
+    let toml_path = path.join("_ricochet.toml");
+    if !toml_path.exists() {
+        bail!(
+            "No _ricochet.toml found in {}. Please create one with `ricochet init`",
+            path.display()
+        );
+    }
+
+    let ricochet_toml = ContentItem::from_toml(&std::fs::read_to_string(&toml_path)?)?;
+    let extra_root_files = resolve_extra_root_files(path, &ricochet_toml.language.packages)?;
+    let plan = crate::utils::plan_bundle(
+        path,
+        ricochet_toml.content.include,
+        ricochet_toml.content.exclude,
+        use_gitignore,
+    )?;
+
+    let mut files = Vec::new();
+    for file in plan.files.iter().filter(|p| p.is_file()) {
+        files.push(BundleFile {
+            path: file
+                .strip_prefix(path)
+                .unwrap_or(file)
+                .display()
+                .to_string(),
+            size: std::fs::metadata(file)?.len(),
+            source: None,
+        });
+    }
+    for (source, name) in &extra_root_files {
+        files.push(BundleFile {
+            path: name.clone(),
+            size: std::fs::metadata(source)?.len(),
+            source: Some(source.display().to_string()),
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut skipped: Vec<SkippedPath> = plan
+        .skipped
+        .into_iter()
+        .map(|(path, reason)| SkippedPath {
+            path: path.display().to_string(),
+            reason,
+        })
+        .collect();
+    skipped.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let (compressed_size, digest) =
+        crate::utils::measure_bundle(path, &plan.files, &extra_root_files)?;
+
+    Ok(BundleReport {
+        total_size: files.iter().map(|f| f.size).sum(),
+        compressed_size,
+        digest: format!("sha256:{digest}"),
+        files,
+        skipped,
+    })
+}
+
+/// Print what `deploy` would bundle from `path` without uploading anything.
+pub fn dry_run(path: &Path, use_gitignore: bool, format: OutputFormat) -> Result<()> {
+    if !path.is_dir() {
+        bail!("Path must be a directory containing _ricochet.toml");
+    }
+
+    let report = plan_deploy_bundle(path, use_gitignore)?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&report)?),
+        OutputFormat::Table => {
+            println!(
+                "📦 Bundle for {} {}\n",
+                path.display().to_string().bright_cyan(),
+                "(dry run, nothing uploaded)".dimmed()
+            );
+
+            // Print each directory once, above the files it contains
+            let mut open_dirs: Vec<&str> = Vec::new();
+            for file in &report.files {
+                let components: Vec<&str> = file.path.split('/').collect();
+                let (name, dirs) = components.split_last().unwrap_or((&"", &[]));
+                let shared = open_dirs
+                    .iter()
+                    .zip(dirs.iter())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                open_dirs.truncate(shared);
+                for dir in &dirs[shared..] {
+                    println!("{}{}/", "  ".repeat(open_dirs.len() + 1), dir.bold());
+                    open_dirs.push(dir);
+                }
+
+                let from = match &file.source {
+                    Some(source) => format!(" (from {source})").dimmed().to_string(),
+                    None => String::new(),
+                };
+                println!(
+                    "{}{}{}  {}",
+                    "  ".repeat(open_dirs.len() + 1),
+                    name,
+                    from,
+                    crate::utils::format_size(file.size).dimmed()
+                );
+            }
+
+            if !report.skipped.is_empty() {
+                println!("\n{}", "Skipped:".bold());
+                for skipped in &report.skipped {
+                    println!(
+                        "  {}  {}",
+                        skipped.path,
+                        skipped.reason.to_string().dimmed()
+                    );
+                }
+            }
+
+            println!(
+                "\n{} {} files, {} uncompressed, {} compressed",
+                "Total:".bold(),
+                report.files.len(),
+                crate::utils::format_size(report.total_size),
+                crate::utils::format_size(report.compressed_size)
+            );
+            println!("{} {}", "Digest:".bold(), report.digest);
+        }
+    }
+
+    Ok(())
+}
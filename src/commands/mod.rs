@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod bundle;
 pub mod config;
 pub mod delete;
 pub mod deploy;
@@ -1,3 +1,4 @@
+use crate::client::RicochetClient;
 use crate::config::{Config, parse_server_url};
 use anyhow::Result;
 use colored::Colorize;
@@ -21,7 +22,7 @@ pub fn list(config: &Config) -> Result<()> {
 
     let mut table = Table::new();
     table.load_style(UTF8_FULL);
-    table.set_header(vec!["Name", "URL", "API Key", "Default"]);
+    table.set_header(vec!["Name", "URL", "API Key", "Key Fingerprint", "Default"]);
 
     // Collect and sort servers by name
     let mut sorted_servers: Vec<_> = servers.into_iter().collect();
@@ -42,6 +43,11 @@ pub fn list(config: &Config) -> Result<()> {
             Cell::new("not set").fg(Color::Red)
         };
 
+        let fingerprint = match &server_config.key_fingerprint {
+            Some(fingerprint) => Cell::new(fingerprint),
+            None => Cell::new("not pinned").fg(Color::DarkGrey),
+        };
+
         let default_marker = if is_default {
             Cell::new("*").fg(Color::Green)
         } else {
@@ -52,6 +58,7 @@ pub fn list(config: &Config) -> Result<()> {
             name_cell,
             Cell::new(server_config.url.as_str()),
             api_key_status,
+            fingerprint,
             default_marker,
         ]);
     }
@@ -170,3 +177,63 @@ pub fn set_default(config: &mut Config, name: String) -> Result<()> {
 
     Ok(())
 }
+
+/// Fetch a server's current env var encryption key and pin its fingerprint,
+/// replacing any earlier pin
+pub async fn trust_key(config: &mut Config, name: String, force: bool) -> Result<()> {
+    let Some(server_config) = config.servers.get(&name) else {
+        anyhow::bail!("Server '{}' not found", name);
+    };
+
+    let client = RicochetClient::new(server_config)?;
+    let server_key = client.fetch_public_key().await?;
+    let pinned = server_config.key_fingerprint.clone();
+
+    if pinned.as_deref() == Some(server_key.fingerprint.as_str()) {
+        println!(
+            "{} Server '{}' already trusts key {}",
+            "✓".green().bold(),
+            name.bright_cyan(),
+            server_key.fingerprint
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Server '{}' ({})",
+        name.bright_cyan(),
+        server_config.url.as_str()
+    );
+    match &pinned {
+        Some(pinned) => println!("  Pinned:  {}", pinned.dimmed()),
+        None => println!("  Pinned:  {}", "none".dimmed()),
+    }
+    println!("  Offered: {}", server_key.fingerprint.bright_cyan());
+
+    // Replacing a pin is exactly what an attacker would want, so ask first
+    if pinned.is_some() && !force {
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Trust the offered key for encrypting environment variables?")
+            .default(false)
+            .interact()?;
+
+        if !confirmed {
+            println!("{}", "Cancelled.".yellow());
+            return Ok(());
+        }
+    }
+
+    if let Some(server_config) = config.servers.get_mut(&name) {
+        server_config.key_fingerprint = Some(server_key.fingerprint.clone());
+    }
+    config.save()?;
+
+    println!(
+        "{} Server '{}' now trusts key {}",
+        "✓".green().bold(),
+        name.bright_cyan(),
+        server_key.fingerprint
+    );
+
+    Ok(())
+}
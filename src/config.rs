@@ -21,6 +21,10 @@ pub struct ServerConfig {
     pub url: Url,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+    /// SHA-256 fingerprint of the server's env var encryption key, pinned
+    /// the first time it is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_fingerprint: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -53,6 +57,7 @@ impl Default for Config {
             ServerConfig {
                 url: Url::parse("http://localhost:3000").unwrap(),
                 api_key: None,
+                key_fingerprint: None,
             },
         );
         Self {
@@ -76,6 +81,7 @@ impl Config {
             ServerConfig {
                 url: server_url,
                 api_key,
+                key_fingerprint: None,
             },
         );
         Self {
@@ -138,8 +144,14 @@ impl Config {
     fn migrate_v1_config(&mut self) {
         if let Some(url) = self.server.take() {
             let api_key = self.api_key.take();
-            self.servers
-                .insert("default".to_string(), ServerConfig { url, api_key });
+            self.servers.insert(
+                "default".to_string(),
+                ServerConfig {
+                    url,
+                    api_key,
+                    key_fingerprint: None,
+                },
+            );
             self.default_server = Some("default".to_string());
         }
     }
@@ -196,7 +208,11 @@ impl Config {
                 }
             }
             // No match, return URL with no API key (user will need to login)
-            return Ok(ServerConfig { url, api_key: None });
+            return Ok(ServerConfig {
+                url,
+                api_key: None,
+                key_fingerprint: None,
+            });
         }
 
         // Not found
@@ -243,11 +259,23 @@ impl Config {
         anyhow::bail!("No servers configured. Use 'ricochet server add' to add a server.")
     }
 
-    /// Add or update a server
+    /// Add or update a server. A pinned key fingerprint is kept as long as
+    /// the URL stays the same.
     pub fn add_server(&mut self, name: impl Into<String>, url: Url, api_key: Option<String>) {
         let name = name.into();
-        self.servers
-            .insert(name.clone(), ServerConfig { url, api_key });
+        let key_fingerprint = self
+            .servers
+            .get(&name)
+            .filter(|existing| existing.url == url)
+            .and_then(|existing| existing.key_fingerprint.clone());
+        self.servers.insert(
+            name.clone(),
+            ServerConfig {
+                url,
+                api_key,
+                key_fingerprint,
+            },
+        );
 
         // Set as default if it's the first server
         if self.default_server.is_none() {
@@ -324,6 +352,33 @@ impl Config {
         let _ = self.save();
     }
 
+    /// Name of the configured server with this URL, if any
+    pub fn server_name_for_url(&self, url: &Url) -> Option<&str> {
+        let mut names: Vec<&String> = self
+            .servers
+            .iter()
+            .filter(|(_, server_config)| server_config.url == *url)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names.first().map(|name| name.as_str())
+    }
+
+    /// Pin a key fingerprint on every configured server with this URL that
+    /// has none yet. Returns false if no configured server has this URL.
+    pub fn pin_key_fingerprint(&mut self, url: &Url, fingerprint: &str) -> bool {
+        let mut found = false;
+        for server_config in self.servers.values_mut() {
+            if server_config.url == *url {
+                found = true;
+                server_config
+                    .key_fingerprint
+                    .get_or_insert_with(|| fingerprint.to_string());
+            }
+        }
+        found
+    }
+
     /// List all configured servers
     pub fn list_servers(&self) -> Vec<(&String, &ServerConfig)> {
         self.servers.iter().collect()
@@ -367,6 +422,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("https://prod.ricochet.com").unwrap(),
                 api_key: Some("rico_prod_key".to_string()),
+                key_fingerprint: None,
             },
         );
         servers.insert(
@@ -374,6 +430,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("https://staging.ricochet.com").unwrap(),
                 api_key: Some("rico_staging_key".to_string()),
+                key_fingerprint: None,
             },
         );
         servers.insert(
@@ -381,6 +438,7 @@ mod tests {
             ServerConfig {
                 url: Url::parse("http://localhost:3000").unwrap(),
                 api_key: None,
+                key_fingerprint: None,
             },
         );
         Config {
@@ -486,6 +544,45 @@ mod tests {
         assert_eq!(server.api_key, Some("new_key".to_string()));
     }
 
+    #[test]
+    fn test_add_server_keeps_pin_only_for_same_url() {
+        cleanup_env();
+        let mut config = create_test_config();
+        let prod_url = Url::parse("https://prod.ricochet.com").unwrap();
+        assert!(config.pin_key_fingerprint(&prod_url, "SHA256:abc"));
+
+        // Logging in again keeps the pin
+        config.add_server("prod", prod_url, Some("rico_new_key".to_string()));
+        assert_eq!(
+            config.servers["prod"].key_fingerprint.as_deref(),
+            Some("SHA256:abc")
+        );
+
+        // Pointing the name at another server drops it
+        let new_url = Url::parse("https://new-prod.ricochet.com").unwrap();
+        config.add_server("prod", new_url, None);
+        assert_eq!(config.servers["prod"].key_fingerprint, None);
+    }
+
+    #[test]
+    fn test_pin_key_fingerprint_keeps_existing_pin() {
+        cleanup_env();
+        let mut config = create_test_config();
+        let prod_url = Url::parse("https://prod.ricochet.com").unwrap();
+
+        assert!(config.pin_key_fingerprint(&prod_url, "SHA256:first"));
+        assert!(config.pin_key_fingerprint(&prod_url, "SHA256:second"));
+        assert_eq!(
+            config.servers["prod"].key_fingerprint.as_deref(),
+            Some("SHA256:first")
+        );
+        assert_eq!(config.server_name_for_url(&prod_url), Some("prod"));
+
+        let unknown = Url::parse("https://typo.ricochet.com").unwrap();
+        assert!(!config.pin_key_fingerprint(&unknown, "SHA256:first"));
+        assert_eq!(config.server_name_for_url(&unknown), None);
+    }
+
     // ==================== remove_server tests ====================
 
     #[test]
@@ -1,22 +1,85 @@
 use anyhow::{Result, bail};
+use aws_lc_rs::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
 use base64::Engine;
-use base64::prelude::BASE64_STANDARD;
-use rsa::pkcs1::DecodeRsaPublicKey;
+use base64::prelude::{BASE64_STANDARD, BASE64_STANDARD_NO_PAD};
+use rsa::pkcs1::{DecodeRsaPublicKey, EncodeRsaPublicKey};
+use rsa::rand_core::{OsRng, RngCore};
 use rsa::sha2::{Digest, Sha256};
 use rsa::traits::PublicKeyParts;
 use rsa::{Oaep, RsaPublicKey};
 use serde::Serialize;
 use std::collections::HashMap;
 
-/// Wire format for the server's `env_vars` multipart field: a JSON object
-/// mapping base64(RSA-OAEP-SHA256(name)) to base64(RSA-OAEP-SHA256(value)).
+/// Response header on `/api/v0/public-key` listing the env var payload
+/// versions the server accepts, e.g. `1, 2`. Servers that predate envelope
+/// encryption don't send it and only accept version 1.
+pub const PAYLOAD_VERSIONS_HEADER: &str = "x-ricochet-env-vars-versions";
+
+/// Version 1 wire format for the server's `env_vars` multipart field: a JSON
+/// object mapping base64(RSA-OAEP-SHA256(name)) to base64(RSA-OAEP-SHA256(value)).
 #[derive(Debug, Serialize)]
 pub struct RsaEncryptedEnvVars(pub HashMap<String, String>);
 
+/// Version 2 wire format. A random AES-256-GCM data key is RSA-OAEP-SHA256
+/// encrypted into `key`, and `vars` maps base64(nonce || AES-GCM(name)) to
+/// base64(nonce || AES-GCM(value)), each with its own 12-byte nonce and the
+/// 16-byte tag appended. Values are not limited by the RSA key size.
+///
+/// Each entry is sealed with associated data (see [`name_aad`] and
+/// [`value_aad`]), so the server must open the name with
+/// `ricochet-env-vars:2:name` and then the value with
+/// `ricochet-env-vars:2:value:` followed by the decrypted name. A value moved under another name, or into a payload of
+/// another version, fails to open.
+#[derive(Debug, Serialize)]
+pub struct EnvelopeEncryptedEnvVars {
+    pub version: u8,
+    pub key: String,
+    pub vars: HashMap<String, String>,
+}
+
+/// An `env_vars` payload in whichever version the server accepts. Both
+/// serialize to the JSON the server expects, so callers send it as is.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum EncryptedEnvVars {
+    Rsa(RsaEncryptedEnvVars),
+    Envelope(EnvelopeEncryptedEnvVars),
+}
+
+/// The server's public key together with the payload versions it accepts.
+#[derive(Debug, Clone)]
+pub struct ServerKey {
+    pub key: RsaPublicKey,
+    pub fingerprint: String,
+    pub supports_envelope: bool,
+}
+
+impl ServerKey {
+    /// Read the value of [`PAYLOAD_VERSIONS_HEADER`]; `None` means the header
+    /// was absent.
+    pub fn new(key: RsaPublicKey, versions: Option<&str>) -> Result<Self> {
+        let supports_envelope =
+            versions.is_some_and(|v| v.split(',').any(|version| version.trim() == "2"));
+        Ok(Self {
+            fingerprint: fingerprint(&key)?,
+            key,
+            supports_envelope,
+        })
+    }
+}
+
 pub fn parse_public_key_pem(pem: &str) -> Result<RsaPublicKey> {
     Ok(RsaPublicKey::from_pkcs1_pem(pem.trim())?)
 }
 
+/// `SHA256:` followed by the unpadded base64 SHA-256 of the key's PKCS#1 DER
+/// encoding, in the style of `ssh-keygen -l`.
+pub fn fingerprint(pub_key: &RsaPublicKey) -> Result<String> {
+    let der = pub_key.to_pkcs1_der()?;
+    let digest = Sha256::digest(der.as_bytes());
+    Ok(format!("SHA256:{}", BASE64_STANDARD_NO_PAD.encode(digest)))
+}
+
 /// The largest plaintext RSA-OAEP with SHA-256 can encrypt under `pub_key`.
 /// 190 bytes for the 2048-bit keys the server issues.
 pub fn max_plaintext_len(pub_key: &RsaPublicKey) -> usize {
@@ -29,7 +92,6 @@ pub fn encrypt_env_vars(
     pub_key: &RsaPublicKey,
     vars: &HashMap<String, String>,
 ) -> Result<RsaEncryptedEnvVars> {
-    use rsa::rand_core::OsRng;
     let max_len = max_plaintext_len(pub_key);
     let mut rng = OsRng;
     let mut out = HashMap::new();
@@ -56,6 +118,72 @@ pub fn encrypt_env_vars(
     Ok(RsaEncryptedEnvVars(out))
 }
 
+/// Associated data for a sealed variable name in a version 2 payload.
+pub fn name_aad() -> Vec<u8> {
+    b"ricochet-env-vars:2:name".to_vec()
+}
+
+/// Associated data for the sealed value of variable `name` in a version 2
+/// payload.
+pub fn value_aad(name: &str) -> Vec<u8> {
+    let mut aad = b"ricochet-env-vars:2:value:".to_vec();
+    aad.extend_from_slice(name.as_bytes());
+    aad
+}
+
+/// Seal `plaintext` under `key` with a fresh random nonce, authenticating
+/// `aad` alongside it, and return nonce || ciphertext || tag.
+fn seal(key: &LessSafeKey, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let mut sealed = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad),
+        &mut sealed,
+    )?;
+    let mut out = nonce.to_vec();
+    out.append(&mut sealed);
+    Ok(out)
+}
+
+/// Encrypt `vars` with a per-request AES-256-GCM data key wrapped under
+/// `pub_key`.
+pub fn encrypt_env_vars_envelope(
+    pub_key: &RsaPublicKey,
+    vars: &HashMap<String, String>,
+) -> Result<EnvelopeEncryptedEnvVars> {
+    let mut data_key = [0u8; 32];
+    OsRng.fill_bytes(&mut data_key);
+    let wrapped = pub_key.encrypt(&mut OsRng, Oaep::new::<Sha256>(), &data_key)?;
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &data_key)?);
+
+    let mut out = HashMap::new();
+    for (name, value) in vars {
+        out.insert(
+            BASE64_STANDARD.encode(seal(&key, name.as_bytes(), &name_aad())?),
+            BASE64_STANDARD.encode(seal(&key, value.as_bytes(), &value_aad(name))?),
+        );
+    }
+    Ok(EnvelopeEncryptedEnvVars {
+        version: 2,
+        key: BASE64_STANDARD.encode(wrapped),
+        vars: out,
+    })
+}
+
+/// Encrypt `vars` in the newest payload version the server accepts.
+pub fn encrypt_for_server(
+    server_key: &ServerKey,
+    vars: &HashMap<String, String>,
+) -> Result<EncryptedEnvVars> {
+    if server_key.supports_envelope {
+        encrypt_env_vars_envelope(&server_key.key, vars).map(EncryptedEnvVars::Envelope)
+    } else {
+        encrypt_env_vars(&server_key.key, vars).map(EncryptedEnvVars::Rsa)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,6 +251,146 @@ mod tests {
         assert!(encrypt_env_vars(&pub_key, &vars).is_ok());
     }
 
+    /// Split nonce || ciphertext || tag and open it with `key` and `aad`, as
+    /// the server does.
+    fn open(key: &LessSafeKey, sealed_b64: &str, aad: &[u8]) -> Option<String> {
+        let sealed = BASE64_STANDARD.decode(sealed_b64).unwrap();
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).unwrap();
+        let mut buf = ciphertext.to_vec();
+        let plaintext = key.open_in_place(nonce, Aad::from(aad), &mut buf).ok()?;
+        Some(String::from_utf8(plaintext.to_vec()).unwrap())
+    }
+
+    /// Unwrap the data key of `encrypted` with `priv_key`.
+    fn data_key(priv_key: &RsaPrivateKey, encrypted: &EnvelopeEncryptedEnvVars) -> LessSafeKey {
+        let wrapped = BASE64_STANDARD.decode(&encrypted.key).unwrap();
+        let data_key = priv_key.decrypt(Oaep::new::<Sha256>(), &wrapped).unwrap();
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &data_key).unwrap())
+    }
+
+    #[test]
+    fn envelope_round_trips_values_past_the_rsa_limit() {
+        let mut rng = OsRng;
+        let priv_key = RsaPrivateKey::new(&mut rng, 2048).unwrap();
+        let pub_key = RsaPublicKey::from(&priv_key);
+
+        let mut vars = HashMap::new();
+        vars.insert("JWT".to_string(), "x".repeat(4096));
+        vars.insert(
+            "TLS_CERT".to_string(),
+            "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n".to_string(),
+        );
+        vars.insert("EMPTY".to_string(), "".to_string());
+
+        let encrypted = encrypt_env_vars_envelope(&pub_key, &vars).unwrap();
+        assert_eq!(encrypted.version, 2);
+        assert_eq!(encrypted.vars.len(), 3);
+
+        // Unwrap the data key with the private key, then open every entry.
+        let key = data_key(&priv_key, &encrypted);
+        let recovered: HashMap<String, String> = encrypted
+            .vars
+            .iter()
+            .map(|(name, value)| {
+                let name = open(&key, name, &name_aad()).unwrap();
+                let value = open(&key, value, &value_aad(&name)).unwrap();
+                (name, value)
+            })
+            .collect();
+        assert_eq!(recovered, vars);
+    }
+
+    #[test]
+    fn envelope_values_only_open_under_their_own_name() {
+        let mut rng = OsRng;
+        let priv_key = RsaPrivateKey::new(&mut rng, 2048).unwrap();
+        let pub_key = RsaPublicKey::from(&priv_key);
+
+        let mut vars = HashMap::new();
+        vars.insert("PUBLIC_URL".to_string(), "https://example.com".to_string());
+        vars.insert("DB_PASSWORD".to_string(), "hunter2".to_string());
+
+        let encrypted = encrypt_env_vars_envelope(&pub_key, &vars).unwrap();
+        let key = data_key(&priv_key, &encrypted);
+        for (name, value) in &encrypted.vars {
+            let name = open(&key, name, &name_aad()).unwrap();
+            let other = if name == "PUBLIC_URL" {
+                "DB_PASSWORD"
+            } else {
+                "PUBLIC_URL"
+            };
+            assert!(open(&key, value, &value_aad(other)).is_none());
+            assert!(open(&key, value, &[]).is_none());
+            // A name can't pass for a value either.
+            assert!(open(&key, value, &name_aad()).is_none());
+        }
+    }
+
+    #[test]
+    fn envelope_uses_a_fresh_nonce_per_entry() {
+        let mut rng = OsRng;
+        let pub_key = RsaPublicKey::from(&RsaPrivateKey::new(&mut rng, 2048).unwrap());
+
+        let mut vars = HashMap::new();
+        vars.insert("A".to_string(), "same".to_string());
+        vars.insert("B".to_string(), "same".to_string());
+
+        let encrypted = encrypt_env_vars_envelope(&pub_key, &vars).unwrap();
+        let values: Vec<&String> = encrypted.vars.values().collect();
+        assert_ne!(values[0], values[1]);
+    }
+
+    #[test]
+    fn picks_the_payload_version_the_server_accepts() {
+        let mut rng = OsRng;
+        let pub_key = RsaPublicKey::from(&RsaPrivateKey::new(&mut rng, 2048).unwrap());
+        let mut vars = HashMap::new();
+        vars.insert("JWT".to_string(), "x".repeat(1000));
+
+        // Servers without the header only take version 1, which can't fit this.
+        let legacy = ServerKey::new(pub_key.clone(), None).unwrap();
+        assert!(!legacy.supports_envelope);
+        assert!(encrypt_for_server(&legacy, &vars).is_err());
+
+        let current = ServerKey::new(pub_key, Some("1, 2")).unwrap();
+        assert!(current.supports_envelope);
+        assert!(matches!(
+            encrypt_for_server(&current, &vars).unwrap(),
+            EncryptedEnvVars::Envelope(_)
+        ));
+    }
+
+    #[test]
+    fn envelope_serializes_with_its_version() {
+        let mut vars = HashMap::new();
+        vars.insert("n".to_string(), "v".to_string());
+        let payload = EncryptedEnvVars::Envelope(EnvelopeEncryptedEnvVars {
+            version: 2,
+            key: "k".to_string(),
+            vars,
+        });
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(json, r#"{"version":2,"key":"k","vars":{"n":"v"}}"#);
+    }
+
+    #[test]
+    fn fingerprint_is_stable_and_distinguishes_keys() {
+        let mut rng = OsRng;
+        let a = RsaPublicKey::from(&RsaPrivateKey::new(&mut rng, 2048).unwrap());
+        let b = RsaPublicKey::from(&RsaPrivateKey::new(&mut rng, 2048).unwrap());
+
+        // Round-tripping through PEM, as the server sends it, keeps the fingerprint
+        let pem = a.to_pkcs1_pem(rsa::pkcs8::LineEnding::LF).unwrap();
+        let parsed = parse_public_key_pem(&pem).unwrap();
+
+        let fp = fingerprint(&a).unwrap();
+        assert!(fp.starts_with("SHA256:"), "{fp}");
+        assert_eq!(fp.len(), "SHA256:".len() + 43);
+        assert_eq!(fingerprint(&parsed).unwrap(), fp);
+        assert_ne!(fingerprint(&b).unwrap(), fp);
+    }
+
     #[test]
     fn serializes_as_flat_json_object() {
         let mut m = HashMap::new();
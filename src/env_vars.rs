@@ -1,29 +1,52 @@
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Parse `.env` / `.Renviron` style `KEY=VALUE` lines into a map.
-pub fn parse_dotenv(content: &str) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
-        }
-        let Some((key, value)) = trimmed.split_once('=') else {
-            continue;
-        };
-        let mut key = key.trim();
-        if let Some(stripped) = key.strip_prefix("export ") {
-            key = stripped.trim();
-        }
-        if !is_valid_key(key) {
-            continue;
-        }
-        let value = strip_quotes(value.trim());
-        map.insert(key.to_string(), value.to_string());
+/// A statement in a dotenv file that could not be parsed. `line` and
+/// `column` are 1-based and point at the offending character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotenvError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for DotenvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
     }
-    map
+}
+
+impl std::error::Error for DotenvError {}
+
+/// Parse a `.env` / `.Renviron` file the way python-dotenv does, skipping
+/// statements it can't parse.
+///
+/// - `export` prefixes, `# comments` on their own line or after a value, and
+///   quoted keys are accepted.
+/// - Single-quoted values are literal apart from `\'` and `\\`.
+/// - Double-quoted values may span lines and decode `\n`, `\t`, `\"` and
+///   the other C escapes.
+/// - Unquoted values run to the end of the line, minus a trailing comment.
+/// - `${NAME}` and `${NAME:-default}` expand to an earlier value in the file,
+///   then the process environment, then the default. Single-quoted values are
+///   not expanded.
+/// - A name with no `=` is ignored, and so is any name that isn't a valid
+///   environment variable name.
+pub fn parse_dotenv(content: &str) -> HashMap<String, String> {
+    DotenvParser::new(content)
+        .parse(false)
+        .expect("lenient parsing skips errors")
+}
+
+/// Like [`parse_dotenv`], but fails on the first statement that can't be
+/// parsed instead of skipping it.
+pub fn parse_dotenv_strict(content: &str) -> Result<HashMap<String, String>, DotenvError> {
+    DotenvParser::new(content).parse(true)
 }
 
 fn is_valid_key(key: &str) -> bool {
@@ -35,16 +58,276 @@ fn is_valid_key(key: &str) -> bool {
     chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
-fn strip_quotes(value: &str) -> &str {
-    let bytes = value.as_bytes();
-    if bytes.len() >= 2
-        && (bytes[0] == b'"' || bytes[0] == b'\'')
-        && bytes[bytes.len() - 1] == bytes[0]
-    {
-        &value[1..value.len() - 1]
-    } else {
-        value
+fn is_inline_space(c: char) -> bool {
+    c.is_whitespace() && c != '\n' && c != '\r'
+}
+
+struct DotenvParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl DotenvParser {
+    fn new(content: &str) -> Self {
+        Self {
+            chars: content.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn at_line_end(&self) -> bool {
+        matches!(self.peek(), None | Some('\n') | Some('\r'))
+    }
+
+    fn skip_inline_space(&mut self) {
+        while self.peek().is_some_and(is_inline_space) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_rest_of_line(&mut self) {
+        while !self.at_line_end() {
+            self.pos += 1;
+        }
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> DotenvError {
+        let before = &self.chars[..pos.min(self.chars.len())];
+        let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+        let line_start = before.iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1);
+        DotenvError {
+            line,
+            column: pos - line_start + 1,
+            message: message.into(),
+        }
+    }
+
+    fn parse(mut self, strict: bool) -> Result<HashMap<String, String>, DotenvError> {
+        let mut map = HashMap::new();
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            match self.peek() {
+                None => break,
+                Some('#') => {
+                    self.skip_rest_of_line();
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            let start = self.pos;
+            match self.binding() {
+                Ok(Some((key, value, expand))) => {
+                    let value = if expand {
+                        expand_variables(&value, &map)
+                    } else {
+                        value
+                    };
+                    map.insert(key, value);
+                }
+                Ok(None) => {}
+                Err(e) if strict => return Err(e),
+                Err(_) => {
+                    // Like python-dotenv, drop the statement and carry on
+                    // with the next line
+                    self.pos = start;
+                    self.skip_rest_of_line();
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// One `[export] KEY=VALUE` statement. Returns the value and whether it
+    /// may be expanded, or `None` for a name without `=`.
+    fn binding(&mut self) -> Result<Option<(String, String, bool)>, DotenvError> {
+        let export: Vec<char> = "export".chars().collect();
+        if self.chars[self.pos..].starts_with(&export)
+            && self
+                .chars
+                .get(self.pos + export.len())
+                .is_some_and(|&c| is_inline_space(c))
+        {
+            self.pos += export.len();
+            self.skip_inline_space();
+        }
+
+        let key_start = self.pos;
+        let key = self.key()?;
+        self.skip_inline_space();
+
+        if self.at_line_end() || self.peek() == Some('#') {
+            self.skip_rest_of_line();
+            return Ok(None);
+        }
+        if self.peek() != Some('=') {
+            return Err(self.error_at(self.pos, format!("expected `=` after `{key}`")));
+        }
+        self.pos += 1;
+        self.skip_inline_space();
+
+        let (value, expand) = match self.peek() {
+            Some(quote @ ('\'' | '"')) => {
+                let value = self.quoted(quote)?;
+                self.skip_inline_space();
+                if self.peek() == Some('#') {
+                    self.skip_rest_of_line();
+                }
+                if !self.at_line_end() {
+                    return Err(self.error_at(self.pos, "unexpected text after closing quote"));
+                }
+                (value, quote == '"')
+            }
+            _ => (self.unquoted(), true),
+        };
+
+        if !is_valid_key(&key) {
+            return Err(self.error_at(
+                key_start,
+                format!("`{key}` is not a valid environment variable name"),
+            ));
+        }
+        Ok(Some((key, value, expand)))
+    }
+
+    fn key(&mut self) -> Result<String, DotenvError> {
+        if let Some(quote @ ('\'' | '"')) = self.peek() {
+            let open = self.pos;
+            self.pos += 1;
+            let mut key = String::new();
+            loop {
+                match self.peek() {
+                    Some(c) if c == quote => break,
+                    Some(c) => key.push(c),
+                    None => return Err(self.error_at(open, "unterminated quoted name")),
+                }
+                self.pos += 1;
+            }
+            self.pos += 1;
+            return Ok(key);
+        }
+
+        let mut key = String::new();
+        while let Some(c) = self.peek()
+            && c != '='
+            && c != '#'
+            && !c.is_whitespace()
+        {
+            key.push(c);
+            self.pos += 1;
+        }
+        if key.is_empty() {
+            return Err(self.error_at(self.pos, "expected a variable name"));
+        }
+        Ok(key)
+    }
+
+    /// A quoted value, with the escapes python-dotenv decodes for that quote.
+    fn quoted(&mut self, quote: char) -> Result<String, DotenvError> {
+        let open = self.pos;
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                let kind = if quote == '"' { "double" } else { "single" };
+                return Err(self.error_at(open, format!("unterminated {kind}-quoted value")));
+            };
+            self.pos += 1;
+            if c == quote {
+                return Ok(value);
+            }
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+            let Some(next) = self.peek() else {
+                value.push(c);
+                continue;
+            };
+            let decoded = match (quote, next) {
+                (_, '\\') => Some('\\'),
+                (_, '\'') => Some('\''),
+                ('"', '"') => Some('"'),
+                ('"', 'a') => Some('\u{07}'),
+                ('"', 'b') => Some('\u{08}'),
+                ('"', 'f') => Some('\u{0C}'),
+                ('"', 'n') => Some('\n'),
+                ('"', 'r') => Some('\r'),
+                ('"', 't') => Some('\t'),
+                ('"', 'v') => Some('\u{0B}'),
+                _ => None,
+            };
+            match decoded {
+                Some(d) => {
+                    value.push(d);
+                    self.pos += 1;
+                }
+                // Unknown escapes are kept as written
+                None => value.push(c),
+            }
+        }
+    }
+
+    /// The rest of the line, without a `#` comment that follows whitespace.
+    fn unquoted(&mut self) -> String {
+        let start = self.pos;
+        self.skip_rest_of_line();
+        let line: String = self.chars[start..self.pos].iter().collect();
+        let mut end = line.len();
+        let mut prev_space = false;
+        for (i, c) in line.char_indices() {
+            if c == '#' && prev_space {
+                end = i;
+                break;
+            }
+            prev_space = c.is_whitespace();
+        }
+        line[..end].trim_end().to_string()
+    }
+}
+
+/// Expand `${NAME}` and `${NAME:-default}`. Earlier values in the file win
+/// over the process environment; a name in neither takes the default, or
+/// the empty string. Anything else, including a bare `$NAME`, is literal.
+fn expand_variables(value: &str, earlier: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(close) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let inner = &after[..close];
+        let (name, default) = match inner.split_once(':') {
+            Some((name, default)) => match default.strip_prefix('-') {
+                Some(default) => (name, default),
+                None => {
+                    // Not a form python-dotenv expands
+                    out.push_str(&rest[start..start + 2 + close + 1]);
+                    rest = &after[close + 1..];
+                    continue;
+                }
+            },
+            None => (inner, ""),
+        };
+        let resolved = earlier
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+            .unwrap_or_else(|| default.to_string());
+        out.push_str(&resolved);
+        rest = &after[close + 1..];
     }
+    out.push_str(rest);
+    out
 }
 
 /// Resolve env var entries into concrete key/value pairs.
@@ -76,9 +359,63 @@ pub fn resolve_env_vars(entries: &[String], dir: &Path) -> Result<HashMap<String
     Ok(result)
 }
 
+/// Dotenv files whose every key is sent, narrowed by `--only`/`--except`
+/// globs on the names.
+#[derive(Debug, Clone, Default)]
+pub struct EnvFiles {
+    pub files: Vec<PathBuf>,
+    pub only: Vec<String>,
+    pub except: Vec<String>,
+}
+
+impl EnvFiles {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Read every file in order, later files overriding earlier ones, and
+    /// keep the names that pass the filters. Filters that leave nothing are
+    /// an error rather than an empty (for `replace`, destructive) request.
+    pub fn load(&self) -> Result<HashMap<String, String>> {
+        let only = crate::utils::build_globset(&self.only).context("Invalid --only pattern")?;
+        let except =
+            crate::utils::build_globset(&self.except).context("Invalid --except pattern")?;
+
+        let mut map = HashMap::new();
+        for file in &self.files {
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let vars = parse_dotenv_strict(&content)
+                .with_context(|| format!("Failed to parse {}", file.display()))?;
+            map.extend(vars);
+        }
+        map.retain(|name, _| {
+            (self.only.is_empty() || only.is_match(name.as_str()))
+                && !except.is_match(name.as_str())
+        });
+
+        if !self.files.is_empty() && map.is_empty() {
+            bail!("No variables in --from-file are left after --only/--except");
+        }
+        Ok(map)
+    }
+}
+
+/// Resolve explicit entries on top of everything in `files`, so a
+/// `KEY=VALUE` on the command line wins over the same key in a file.
+pub fn resolve_with_files(
+    entries: &[String],
+    files: &EnvFiles,
+    dir: &Path,
+) -> Result<HashMap<String, String>> {
+    let mut map = files.load()?;
+    map.extend(resolve_env_vars(entries, dir)?);
+    Ok(map)
+}
+
 /// Load `.Renviron` then `.env` so that `.env` values take precedence.
 /// Missing files are treated as empty.
-fn load_dotfiles(dir: &Path) -> HashMap<String, String> {
+pub(crate) fn load_dotfiles(dir: &Path) -> HashMap<String, String> {
     let mut map = HashMap::new();
     for name in [".Renviron", ".env"] {
         if let Ok(content) = std::fs::read_to_string(dir.join(name)) {
@@ -117,6 +454,188 @@ NOT A KEY=ignored
         assert_eq!(map.len(), 4);
     }
 
+    /// Inputs and what python-dotenv reads from them. Names it would accept
+    /// but that aren't valid environment variable names are dropped.
+    const CORPUS: &[(&str, &[(&str, &str)])] = &[
+        // Names and the `=`
+        ("a=b", &[("a", "b")]),
+        (" a = b ", &[("a", "b")]),
+        ("export a=b", &[("a", "b")]),
+        ("export \t a=b", &[("a", "b")]),
+        ("export=1", &[("export", "1")]),
+        ("'a'=b", &[("a", "b")]),
+        ("\"a\"=b", &[("a", "b")]),
+        ("a=b=c", &[("a", "b=c")]),
+        ("a", &[]),
+        ("a # no value", &[]),
+        ("123BAD=nope", &[]),
+        ("a-b=c", &[]),
+        // Comments and blank lines
+        ("# comment\na=b", &[("a", "b")]),
+        ("  # indented comment\na=b", &[("a", "b")]),
+        ("a=b # comment", &[("a", "b")]),
+        ("a=b\t# comment", &[("a", "b")]),
+        ("a=b#not-a-comment", &[("a", "b#not-a-comment")]),
+        ("a=\"b\" # comment", &[("a", "b")]),
+        ("a='b' # comment", &[("a", "b")]),
+        ("a=\"b # not a comment\"", &[("a", "b # not a comment")]),
+        ("a=b\n\n\nc=d", &[("a", "b"), ("c", "d")]),
+        ("a=b\r\nc=d\r\n", &[("a", "b"), ("c", "d")]),
+        // Unquoted values
+        ("a=", &[("a", "")]),
+        ("a=   ", &[("a", "")]),
+        ("a=  b c  ", &[("a", "b c")]),
+        ("a=b\\nc", &[("a", "b\\nc")]),
+        // Single quotes keep everything but \' and \\
+        ("a=''", &[("a", "")]),
+        ("a='b c'", &[("a", "b c")]),
+        ("a='b\\nc'", &[("a", "b\\nc")]),
+        ("a='b\\'c'", &[("a", "b'c")]),
+        ("a='b\\\\c'", &[("a", "b\\c")]),
+        ("a='b \"c\"'", &[("a", "b \"c\"")]),
+        ("a='line1\nline2'", &[("a", "line1\nline2")]),
+        // Double quotes decode escapes and may span lines
+        ("a=\"\"", &[("a", "")]),
+        ("a=\"b c\"", &[("a", "b c")]),
+        ("a=\"b\\nc\"", &[("a", "b\nc")]),
+        ("a=\"b\\tc\\r\"", &[("a", "b\tc\r")]),
+        ("a=\"b\\\"c\"", &[("a", "b\"c")]),
+        ("a=\"b\\'c\"", &[("a", "b'c")]),
+        ("a=\"b\\\\c\"", &[("a", "b\\c")]),
+        ("a=\"b\\xc\"", &[("a", "b\\xc")]),
+        (
+            "a=\"line1\nline2\"\nc=d",
+            &[("a", "line1\nline2"), ("c", "d")],
+        ),
+        (
+            "CERT=\"-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n\"",
+            &[(
+                "CERT",
+                "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n",
+            )],
+        ),
+        (
+            "SA='{\"type\": \"service_account\", \"key\": \"a\\nb\"}'",
+            &[("SA", "{\"type\": \"service_account\", \"key\": \"a\\nb\"}")],
+        ),
+        // Interpolation
+        ("a=b\nc=${a}", &[("a", "b"), ("c", "b")]),
+        ("a=b\nc=\"${a}/x\"", &[("a", "b"), ("c", "b/x")]),
+        ("a=b\nc='${a}'", &[("a", "b"), ("c", "${a}")]),
+        ("a=b\nc=$a", &[("a", "b"), ("c", "$a")]),
+        ("a=b\nc=${a:-d}", &[("a", "b"), ("c", "b")]),
+        ("c=${RICO_DOTENV_UNSET:-fallback}", &[("c", "fallback")]),
+        ("c=${RICO_DOTENV_UNSET}", &[("c", "")]),
+        ("a=\nc=${a:-d}", &[("a", ""), ("c", "")]),
+        ("c=${a:x}", &[("c", "${a:x}")]),
+        ("c=${a", &[("c", "${a")]),
+        (
+            "c=${RICO_DOTENV_LATER}\nRICO_DOTENV_LATER=b",
+            &[("c", ""), ("RICO_DOTENV_LATER", "b")],
+        ),
+        // Later statements win
+        ("a=1\na=2", &[("a", "2")]),
+        // Statements that can't be parsed are skipped
+        ("NOT A KEY=ignored\nb=c", &[("b", "c")]),
+        ("a=\"b\" c\nd=e", &[("d", "e")]),
+        ("a=\"b\"\"c\"\nd=e", &[("d", "e")]),
+        ("a=\"unterminated\nd=e", &[("d", "e")]),
+        ("=b\nd=e", &[("d", "e")]),
+    ];
+
+    #[test]
+    fn parses_the_corpus_like_python_dotenv() {
+        for (input, expected) in CORPUS {
+            let expected: HashMap<String, String> = expected
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            assert_eq!(parse_dotenv(input), expected, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn interpolation_prefers_the_file_over_the_environment() {
+        // SAFETY: single-threaded test; keys are unique to this test.
+        unsafe {
+            std::env::set_var("RICO_DOTENV_FROM_ENV", "env_value");
+            std::env::set_var("RICO_DOTENV_SHADOWED", "env_value");
+        }
+
+        let map = parse_dotenv(
+            "RICO_DOTENV_SHADOWED=file_value\na=${RICO_DOTENV_FROM_ENV}\nb=${RICO_DOTENV_SHADOWED}\n",
+        );
+        assert_eq!(map.get("a"), Some(&"env_value".to_string()));
+        assert_eq!(map.get("b"), Some(&"file_value".to_string()));
+
+        unsafe {
+            std::env::remove_var("RICO_DOTENV_FROM_ENV");
+            std::env::remove_var("RICO_DOTENV_SHADOWED");
+        }
+    }
+
+    #[test]
+    fn strict_mode_reports_line_and_column() {
+        let cases = [
+            ("a=b\nNOT A KEY=x", 2, 5, "expected `=` after `NOT`"),
+            ("a=\"b\" c", 1, 7, "unexpected text after closing quote"),
+            (
+                "a=b\nc=\"unterminated\nd=e",
+                2,
+                3,
+                "unterminated double-quoted value",
+            ),
+            ("a='x", 1, 3, "unterminated single-quoted value"),
+            ("'a=b", 1, 1, "unterminated quoted name"),
+            (
+                "a=b\n  123BAD=x",
+                2,
+                3,
+                "`123BAD` is not a valid environment variable name",
+            ),
+            ("  =x", 1, 3, "expected a variable name"),
+        ];
+        for (input, line, column, message) in cases {
+            let err = parse_dotenv_strict(input).unwrap_err();
+            assert_eq!(
+                err,
+                DotenvError {
+                    line,
+                    column,
+                    message: message.to_string(),
+                },
+                "input: {input:?}"
+            );
+        }
+
+        let err = parse_dotenv_strict("a=\"b\" c").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 7: unexpected text after closing quote"
+        );
+    }
+
+    #[test]
+    fn strict_mode_agrees_with_lenient_on_valid_input() {
+        let input = "export A=1\nB='two'\nC=\"multi\nline\" # note\nD=${A}-x\n";
+        assert_eq!(parse_dotenv_strict(input).unwrap(), parse_dotenv(input));
+    }
+
+    #[test]
+    fn from_file_reports_where_a_file_fails_to_parse() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("prod.env");
+        fs::write(&file, "A=1\nB=\"unterminated\n").unwrap();
+
+        let files = EnvFiles {
+            files: vec![file],
+            ..Default::default()
+        };
+        let err = format!("{:#}", files.load().unwrap_err());
+        assert!(err.contains("prod.env"), "{err}");
+        assert!(err.contains("line 2, column 3"), "{err}");
+    }
+
     #[test]
     fn key_value_entry_used_literally() {
         let dir = TempDir::new().unwrap();
@@ -176,6 +695,59 @@ NOT A KEY=ignored
         assert!(err.contains("`DEFINITELY_MISSING_KEY_XYZ=value`"));
     }
 
+    #[test]
+    fn from_file_takes_every_key_and_later_files_win() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join("base.env");
+        let prod = dir.path().join("prod.env");
+        fs::write(&base, "DB_URL=base\nDB_POOL=5\nLOG_LEVEL=debug\n").unwrap();
+        fs::write(&prod, "DB_URL=prod\n").unwrap();
+
+        let files = EnvFiles {
+            files: vec![base, prod],
+            ..Default::default()
+        };
+        let map = resolve_with_files(&["LOG_LEVEL=info".to_string()], &files, dir.path()).unwrap();
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("DB_URL"), Some(&"prod".to_string()));
+        assert_eq!(map.get("DB_POOL"), Some(&"5".to_string()));
+        // Explicit entries override the files
+        assert_eq!(map.get("LOG_LEVEL"), Some(&"info".to_string()));
+    }
+
+    #[test]
+    fn from_file_applies_only_and_except() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join(".env");
+        fs::write(&file, "DB_URL=a\nDB_PASSWORD=b\nAPI_KEY=c\n").unwrap();
+
+        let files = EnvFiles {
+            files: vec![file.clone()],
+            only: vec!["DB_*".to_string()],
+            except: vec!["*PASSWORD".to_string()],
+        };
+        let map = files.load().unwrap();
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["DB_URL"]);
+
+        let none_left = EnvFiles {
+            files: vec![file],
+            only: vec!["NOPE_*".to_string()],
+            ..Default::default()
+        };
+        assert!(none_left.load().is_err());
+    }
+
+    #[test]
+    fn from_file_missing_file_names_it() {
+        let files = EnvFiles {
+            files: vec![PathBuf::from("/definitely/missing/.env")],
+            ..Default::default()
+        };
+        let err = format!("{:#}", files.load().unwrap_err());
+        assert!(err.contains("/definitely/missing/.env"), "{err}");
+    }
+
     #[test]
     fn later_flag_overrides_earlier() {
         let dir = TempDir::new().unwrap();
@@ -1,10 +1,15 @@
 use crate::{OutputFormat, client::RicochetClient, config::Config};
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use anyhow::{Result, bail};
 use colored::Colorize;
 use comfy_table::{Cell, Color, Table, presets::UTF8_FULL};
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
 use ricochet_core::events::DeploymentStatus;
 use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::Duration;
+
+/// How often to poll a pending deployment or invocation for its status.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DeploymentRow {
@@ -17,6 +22,12 @@ pub struct DeploymentRow {
     pub requested_ver: Option<String>,
     pub matched_ver: Option<String>,
     pub git_hash: Option<String>,
+    /// Whether this deployment is the one serving the app, if the server says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    /// The deployment this one re-deployed, if it was created by a rollback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_of: Option<String>,
 }
 
 const DEFAULT_FIELDS: &[&str] = &["id", "status", "deployed_at"];
@@ -33,9 +44,7 @@ const ALL_FIELDS: &[&str] = &[
 ];
 
 fn format_deployed_at(ts: i64) -> String {
-    DateTime::<Utc>::from_timestamp(ts, 0)
-        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
-        .unwrap_or_else(|| ts.to_string())
+    super::format_unix_time(ts)
 }
 
 fn status_cell(status: &DeploymentStatus) -> Cell {
@@ -51,15 +60,7 @@ fn status_cell(status: &DeploymentStatus) -> Cell {
 }
 
 fn resolve_fields(fields: Option<Vec<String>>) -> Vec<&'static str> {
-    match fields {
-        None => DEFAULT_FIELDS.to_vec(),
-        Some(f) if f.len() == 1 && f[0] == "all" => ALL_FIELDS.to_vec(),
-        Some(f) => ALL_FIELDS
-            .iter()
-            .copied()
-            .filter(|col| f.iter().any(|s| s == col))
-            .collect(),
-    }
+    super::resolve_fields(fields, DEFAULT_FIELDS, ALL_FIELDS)
 }
 
 fn field_header(field: &str) -> &'static str {
@@ -171,3 +172,333 @@ pub async fn get_deployment(
 
     Ok(())
 }
+
+/// Poll a deployment until it leaves `Pending`, showing a spinner with the
+/// elapsed time. Returns the deployment in its final state.
+pub async fn wait_for_deployment(
+    client: &RicochetClient,
+    deployment_ulid: &str,
+) -> Result<DeploymentRow> {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg} [{elapsed}]")?);
+    pb.enable_steady_tick(Duration::from_millis(80));
+    pb.set_message(format!(
+        "Waiting for deployment {} to finish",
+        deployment_ulid.bright_cyan()
+    ));
+
+    loop {
+        let d = match client.get_deployment(deployment_ulid).await {
+            Ok(d) => d,
+            Err(e) => {
+                pb.finish_and_clear();
+                return Err(e);
+            }
+        };
+        if !matches!(d.status, DeploymentStatus::Pending) {
+            pb.finish_and_clear();
+            return Ok(d);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Print the outcome of a finished deployment, failing unless it succeeded.
+pub fn report_outcome(d: &DeploymentRow, elapsed: Duration) -> Result<()> {
+    match d.status {
+        DeploymentStatus::Success => {
+            println!(
+                "{} Deployment {} succeeded after {}",
+                "✓".green().bold(),
+                d.id.bright_cyan(),
+                HumanDuration(elapsed)
+            );
+            Ok(())
+        }
+        _ => {
+            eprintln!(
+                "{} Deployment {} finished with status {} after {}",
+                "✗".red().bold(),
+                d.id.bright_cyan(),
+                d.status.to_string().red(),
+                HumanDuration(elapsed)
+            );
+            bail!("Deployment {} did not succeed: {}", d.id, d.status)
+        }
+    }
+}
+
+/// The part of `log` not yet printed, given that the first `printed` bytes
+/// already were. Falls back to the whole log if it was truncated or rewritten.
+pub(crate) fn unseen_output(log: &str, printed: usize) -> &str {
+    log.get(printed..).unwrap_or(log)
+}
+
+/// How much of `buf` can be printed now: everything but a multi-byte
+/// character cut off at the end, which the next fetch completes.
+pub(crate) fn complete_utf8_len(buf: &[u8]) -> usize {
+    match std::str::from_utf8(buf) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => buf.len(),
+    }
+}
+
+/// Print a deployment's build log as it grows until the deployment leaves
+/// `Pending`, fetching only the bytes not yet seen. Returns the deployment
+/// in its final state.
+pub async fn follow_deployment_logs(
+    client: &RicochetClient,
+    deployment_ulid: &str,
+) -> Result<DeploymentRow> {
+    let mut offset = 0;
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        // Read the status before the log so the last fetch holds everything
+        // written before the deployment finished
+        let d = client.get_deployment(deployment_ulid).await?;
+        let (start, new) = client
+            .get_deployment_logs_from(deployment_ulid, offset)
+            .await?;
+        if start != offset {
+            // The log was rewritten, so print it again from the top
+            pending.clear();
+        }
+        offset = start + new.len();
+        pending.extend_from_slice(&new);
+
+        let finished = !matches!(d.status, DeploymentStatus::Pending);
+        let printable = if finished {
+            pending.len()
+        } else {
+            complete_utf8_len(&pending)
+        };
+        print!("{}", String::from_utf8_lossy(&pending[..printable]));
+        std::io::stdout().flush()?;
+        pending.drain(..printable);
+
+        if finished {
+            return Ok(d);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+pub async fn deployment_logs(
+    config: &Config,
+    server_ref: Option<&str>,
+    deployment_ulid: &str,
+    follow: bool,
+) -> Result<()> {
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    if follow {
+        let d = follow_deployment_logs(&client, deployment_ulid).await?;
+        eprintln!(
+            "\n{} Deployment {} finished with status {}",
+            "→".bright_cyan(),
+            d.id.bright_cyan(),
+            d.status
+        );
+        if !matches!(d.status, DeploymentStatus::Success) {
+            bail!("Deployment {} did not succeed: {}", d.id, d.status);
+        }
+    } else {
+        print!("{}", client.get_deployment_logs(deployment_ulid).await?);
+    }
+
+    Ok(())
+}
+
+/// The deployment to roll back to by default: the last successful release
+/// before the live one. The live deployment is the one the server marks
+/// active, or else the most recent success. A deployment created by a
+/// rollback serves the release it re-deployed, so releases are counted from
+/// that one, and rollbacks are never targets themselves.
+fn previous_success(deployments: &[DeploymentRow]) -> Option<&DeploymentRow> {
+    let succeeded = |d: &&DeploymentRow| matches!(d.status, DeploymentStatus::Success);
+    let live = deployments
+        .iter()
+        .find(|d| d.active == Some(true))
+        .or_else(|| {
+            deployments
+                .iter()
+                .filter(succeeded)
+                .max_by_key(|d| d.deployed_at)
+        })?;
+    let release = live
+        .rollback_of
+        .as_ref()
+        .and_then(|id| deployments.iter().find(|d| d.id == *id))
+        .unwrap_or(live);
+
+    deployments
+        .iter()
+        .filter(succeeded)
+        .filter(|d| d.rollback_of.is_none() && d.deployed_at < release.deployed_at)
+        .max_by_key(|d| d.deployed_at)
+}
+
+pub async fn rollback(
+    config: &Config,
+    server_ref: Option<&str>,
+    content_ulid: &str,
+    to: Option<&str>,
+    force: bool,
+    wait: bool,
+) -> Result<()> {
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let deployments = client.list_deployments(content_ulid).await?;
+    let target = match to {
+        Some(id) => {
+            let Some(d) = deployments.iter().find(|d| d.id == id) else {
+                bail!(
+                    "Deployment {} not found for content item {}",
+                    id,
+                    content_ulid
+                );
+            };
+            if !matches!(d.status, DeploymentStatus::Success) {
+                bail!(
+                    "Deployment {} finished with status {}; only successful deployments can be rolled back to",
+                    id,
+                    d.status
+                );
+            }
+            d
+        }
+        None => match previous_success(&deployments) {
+            Some(d) => d,
+            None => bail!(
+                "No earlier successful deployment of {} to roll back to",
+                content_ulid
+            ),
+        },
+    };
+
+    if !force {
+        let message = format!(
+            "Roll back {} to deployment {} from {}?",
+            content_ulid,
+            target.id,
+            format_deployed_at(target.deployed_at)
+        );
+        if !crate::utils::confirm(&message)? {
+            println!("{}", "Rollback cancelled".yellow());
+            return Ok(());
+        }
+    }
+
+    let d = client.rollback(content_ulid, &target.id).await?;
+    println!(
+        "{} Rolling back to {} as deployment {}",
+        "✓".green().bold(),
+        target.id.bright_cyan(),
+        d.id.bright_cyan()
+    );
+
+    if wait {
+        let started = std::time::Instant::now();
+        let finished = wait_for_deployment(&client, &d.id).await?;
+        report_outcome(&finished, started.elapsed())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unseen_output_skips_what_was_already_printed() {
+        assert_eq!(unseen_output("one\ntwo\n", 0), "one\ntwo\n");
+        assert_eq!(unseen_output("one\ntwo\n", 4), "two\n");
+        assert_eq!(unseen_output("one\ntwo\n", 8), "");
+    }
+
+    #[test]
+    fn unseen_output_reprints_a_truncated_log() {
+        assert_eq!(unseen_output("fresh\n", 20), "fresh\n");
+    }
+
+    fn deployment(id: &str, deployed_at: i64, status: DeploymentStatus) -> DeploymentRow {
+        DeploymentRow {
+            id: id.to_string(),
+            content_id: "content".to_string(),
+            deployed_at,
+            status,
+            deployed_by: "user".to_string(),
+            ip_address: "127.0.0.1".to_string(),
+            requested_ver: None,
+            matched_ver: None,
+            git_hash: None,
+            active: None,
+            rollback_of: None,
+        }
+    }
+
+    #[test]
+    fn previous_success_skips_the_live_deployment_and_failures() {
+        let deployments = vec![
+            deployment("oldest", 1, DeploymentStatus::Success),
+            deployment("live", 3, DeploymentStatus::Success),
+            deployment("broken", 2, DeploymentStatus::Failure),
+            deployment("latest", 4, DeploymentStatus::Failure),
+        ];
+        assert_eq!(previous_success(&deployments).unwrap().id, "oldest");
+    }
+
+    #[test]
+    fn second_rollback_goes_further_back_when_rollbacks_are_new_deployments() {
+        let mut rollback = deployment("rollback", 4, DeploymentStatus::Success);
+        rollback.rollback_of = Some("good".to_string());
+        let deployments = vec![
+            deployment("older", 1, DeploymentStatus::Success),
+            deployment("good", 2, DeploymentStatus::Success),
+            deployment("broken", 3, DeploymentStatus::Success),
+            rollback,
+        ];
+        assert_eq!(previous_success(&deployments).unwrap().id, "older");
+    }
+
+    #[test]
+    fn second_rollback_goes_further_back_when_the_server_marks_the_live_one() {
+        let mut good = deployment("good", 2, DeploymentStatus::Success);
+        good.active = Some(true);
+        let deployments = vec![
+            deployment("older", 1, DeploymentStatus::Success),
+            good,
+            deployment("broken", 3, DeploymentStatus::Success),
+        ];
+        assert_eq!(previous_success(&deployments).unwrap().id, "older");
+    }
+
+    #[test]
+    fn previous_success_needs_two_successes() {
+        let deployments = vec![
+            deployment("live", 2, DeploymentStatus::Success),
+            deployment("broken", 1, DeploymentStatus::Failure),
+        ];
+        assert!(previous_success(&deployments).is_none());
+    }
+
+    #[test]
+    fn complete_utf8_len_holds_back_a_split_character() {
+        let text = "ok é".as_bytes();
+        assert_eq!(complete_utf8_len(text), text.len());
+        assert_eq!(complete_utf8_len(&text[..text.len() - 1]), 3);
+        // Invalid bytes mid-log are printed lossily rather than held forever
+        assert_eq!(complete_utf8_len(b"a\xffb"), 3);
+    }
+
+    #[test]
+    fn unseen_output_never_splits_a_character() {
+        // "é" is two bytes; an offset inside it means the log was rewritten
+        assert_eq!(unseen_output("é", 1), "é");
+    }
+}
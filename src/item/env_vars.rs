@@ -1,9 +1,92 @@
-use crate::{OutputFormat, client::RicochetClient, config::Config, item::resolve_id, utils};
-use anyhow::Result;
+use crate::{
+    OutputFormat,
+    client::RicochetClient,
+    config::Config,
+    env_vars::EnvFiles,
+    item::{load_local, resolve_id},
+    utils,
+};
+use anyhow::{Context, Result};
 use colored::Colorize;
-use comfy_table::{Table, presets::UTF8_FULL};
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
+/// `env-vars diff --exit-code` found names the server is missing. The CLI
+/// exits with status 1.
+#[derive(Debug)]
+pub struct EnvVarsDrift {
+    pub names: Vec<String>,
+}
+
+impl std::fmt::Display for EnvVarsDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Server is missing {} environment variable(s): {}",
+            self.names.len(),
+            self.names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for EnvVarsDrift {}
+
+/// How the names set on an item compare with the local dotfiles and the
+/// `[env_vars]` of `_ricochet.toml`. Every name lands in exactly one list.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct EnvVarsDiff {
+    /// In a local dotfile but not set on the server
+    pub missing: Vec<String>,
+    /// Declared in `_ricochet.toml` but not set on the server
+    pub declared_unset: Vec<String>,
+    /// Set on the server but in neither a dotfile nor `_ricochet.toml`
+    pub extra: Vec<String>,
+    /// Set on the server and present locally
+    pub in_sync: Vec<String>,
+}
+
+impl EnvVarsDiff {
+    /// Names the server needs but doesn't have
+    fn unset(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .missing
+            .iter()
+            .chain(&self.declared_unset)
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+fn diff_names(
+    server: &BTreeSet<String>,
+    local: &BTreeSet<String>,
+    declared: &BTreeSet<String>,
+) -> EnvVarsDiff {
+    let mut diff = EnvVarsDiff::default();
+    for name in declared {
+        if !server.contains(name) {
+            diff.declared_unset.push(name.clone());
+        }
+    }
+    for name in local.difference(declared) {
+        if !server.contains(name) {
+            diff.missing.push(name.clone());
+        }
+    }
+    for name in server {
+        if local.contains(name) || declared.contains(name) {
+            diff.in_sync.push(name.clone());
+        } else {
+            diff.extra.push(name.clone());
+        }
+    }
+    diff
+}
+
 /// Directory to resolve `.env` / `.Renviron` lookups against for a bare `KEY`
 /// entry: the directory containing `path`, if given, otherwise the current
 /// directory.
@@ -96,31 +179,132 @@ pub async fn delete_env_var(
     print_names(server_config.url.as_str(), &names, format)
 }
 
+/// Fetch the server's env var encryption key and check it against the pin in
+/// the config file, read afresh so a pin saved since the command started
+/// counts. The first key seen for a configured server is pinned; a different
+/// key afterwards is refused until the user runs `ricochet server trust-key`.
+/// A server that isn't configured has nowhere to keep a pin, so its key is
+/// only used if the user confirms it or passes `trust_unpinned`.
+pub async fn pinned_server_key(
+    client: &RicochetClient,
+    trust_unpinned: bool,
+) -> Result<crate::crypto::ServerKey> {
+    let server_key = client.fetch_public_key().await?;
+    let url = &client.base_url;
+    let server_url = url.as_str().trim_end_matches('/');
+    let mut config = Config::load()?;
+
+    let changed = config
+        .servers
+        .iter()
+        .filter(|(_, server_config)| server_config.url == *url)
+        .find_map(|(name, server_config)| {
+            server_config
+                .key_fingerprint
+                .as_ref()
+                .filter(|pinned| **pinned != server_key.fingerprint)
+                .map(|pinned| (name, pinned))
+        });
+    if let Some((name, pinned)) = changed {
+        let trust_cmd = format!("ricochet server trust-key {name}").bright_cyan();
+        anyhow::bail!(
+            "The encryption key for {server_url} has changed.\n  Pinned:  {pinned}\n  Offered: {}\nRefusing to encrypt environment variables with it. If the server's key was rotated on purpose, run {trust_cmd}.",
+            server_key.fingerprint
+        );
+    }
+
+    if config.server_name_for_url(url).is_none() {
+        eprintln!(
+            "{} {} is not a configured server, so its encryption key {} can't be checked against a pinned one",
+            "⚠".yellow(),
+            server_url,
+            server_key.fingerprint.dimmed()
+        );
+        if !trust_unpinned
+            && (utils::is_non_interactive()
+                || !utils::confirm("Encrypt environment variables with this key?")?)
+        {
+            anyhow::bail!(
+                "Refusing to encrypt environment variables with an unpinned key. Add the server with {} so its key can be pinned, or pass --trust-unpinned-key",
+                "ricochet server add".bright_cyan()
+            );
+        }
+        return Ok(server_key);
+    }
+
+    let unpinned = config
+        .servers
+        .values()
+        .any(|server_config| server_config.url == *url && server_config.key_fingerprint.is_none());
+    if unpinned {
+        config.pin_key_fingerprint(url, &server_key.fingerprint);
+        config.save()?;
+        eprintln!(
+            "{} Pinned encryption key for {}: {}",
+            "→".bright_cyan(),
+            server_url,
+            server_key.fingerprint.dimmed()
+        );
+    }
+
+    Ok(server_key)
+}
+
 /// Encrypt the resolved entries with the server's public key.
 async fn encrypt_entries(
     client: &RicochetClient,
-    env: &[String],
-    dir: &Path,
-) -> Result<crate::crypto::RsaEncryptedEnvVars> {
-    let resolved = crate::env_vars::resolve_env_vars(env, dir)?;
-    let pub_key = client.get_public_key().await?;
-    crate::crypto::encrypt_env_vars(&pub_key, &resolved)
+    resolved: &HashMap<String, String>,
+    trust_unpinned: bool,
+) -> Result<crate::crypto::EncryptedEnvVars> {
+    let server_key = pinned_server_key(client, trust_unpinned).await?;
+    crate::crypto::encrypt_for_server(&server_key, resolved)
 }
 
+/// List the names about to be sent, never their values, so a whole dotenv
+/// file can be checked before it leaves the machine.
+pub(crate) fn print_pending_names(resolved: &HashMap<String, String>) {
+    let mut names: Vec<&String> = resolved.keys().collect();
+    names.sort();
+    eprintln!(
+        "Sending {} environment variable(s):",
+        names.len().to_string().bright_cyan()
+    );
+    for name in names {
+        eprintln!("  {name}");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn set_env_vars(
     config: &Config,
     server_ref: Option<&str>,
     id: Option<&str>,
     path: Option<&Path>,
     env: &[String],
+    files: &EnvFiles,
+    force: bool,
+    trust_unpinned_key: bool,
     format: OutputFormat,
 ) -> Result<()> {
     let id = resolve_id(id, path)?;
+    let resolved = crate::env_vars::resolve_with_files(env, files, &env_dir(path))?;
+
+    if !files.is_empty() {
+        print_pending_names(&resolved);
+        if !force
+            && !utils::confirm("Send these environment variables?")
+                .context("Pass --force to send them without asking")?
+        {
+            eprintln!("{}", "Set cancelled".yellow());
+            return Ok(());
+        }
+    }
+
     let server_config = config.resolve_server(server_ref)?;
     let client = RicochetClient::new(&server_config)?;
     client.preflight_key_check().await?;
 
-    let encrypted = encrypt_entries(&client, env, &env_dir(path)).await?;
+    let encrypted = encrypt_entries(&client, &resolved, trust_unpinned_key).await?;
     let names = client.upsert_env_vars(&id, &encrypted).await?;
 
     eprintln!(
@@ -131,17 +315,24 @@ pub async fn set_env_vars(
     print_names(server_config.url.as_str(), &names, format)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn replace_env_vars(
     config: &Config,
     server_ref: Option<&str>,
     id: Option<&str>,
     path: Option<&Path>,
     env: &[String],
+    files: &EnvFiles,
     force: bool,
+    trust_unpinned_key: bool,
     format: OutputFormat,
 ) -> Result<()> {
     let id = resolve_id(id, path)?;
+    let resolved = crate::env_vars::resolve_with_files(env, files, &env_dir(path))?;
 
+    if !files.is_empty() {
+        print_pending_names(&resolved);
+    }
     if !force {
         let message =
             "This replaces all environment variables. Anything not listed gets deleted. Continue?";
@@ -155,7 +346,7 @@ pub async fn replace_env_vars(
     let client = RicochetClient::new(&server_config)?;
     client.preflight_key_check().await?;
 
-    let encrypted = encrypt_entries(&client, env, &env_dir(path)).await?;
+    let encrypted = encrypt_entries(&client, &resolved, trust_unpinned_key).await?;
     let names = client.replace_env_vars(&id, &encrypted).await?;
 
     eprintln!(
@@ -165,3 +356,126 @@ pub async fn replace_env_vars(
 
     print_names(server_config.url.as_str(), &names, format)
 }
+
+/// Names in the given dotenv files, or in `.env` and `.Renviron` next to
+/// `_ricochet.toml` when none are given.
+fn local_names(files: &[PathBuf], path: Option<&Path>) -> Result<BTreeSet<String>> {
+    if files.is_empty() {
+        return Ok(crate::env_vars::load_dotfiles(&env_dir(path))
+            .into_keys()
+            .collect());
+    }
+    let mut names = BTreeSet::new();
+    for file in files {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let vars = crate::env_vars::parse_dotenv_strict(&content)
+            .with_context(|| format!("Failed to parse {}", file.display()))?;
+        names.extend(vars.into_keys());
+    }
+    Ok(names)
+}
+
+/// Compare the names set on an item with the local dotfiles and the
+/// `[env_vars]` declared in `_ricochet.toml`. With `exit_code`, fail with
+/// [`EnvVarsDrift`] when the server lacks any of them.
+pub async fn diff_env_vars(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    files: &[PathBuf],
+    exit_code: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let local = local_names(files, path)?;
+    // Only a toml describing this item says what it should have
+    let declared: BTreeSet<String> = match load_local(path) {
+        Ok((local_id, item)) if local_id == id => {
+            item.env_vars.unwrap_or_default().into_keys().collect()
+        }
+        _ => BTreeSet::new(),
+    };
+
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let server: BTreeSet<String> = client.get_env_vars(&id).await?.into_iter().collect();
+    let diff = diff_names(&server, &local, &declared);
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&diff)?),
+        OutputFormat::Table => {
+            println!("{}", server_config.url.as_str().italic().dimmed());
+
+            let rows = [
+                (&diff.missing, "missing on server", Color::Red),
+                (&diff.declared_unset, "declared but unset", Color::Yellow),
+                (&diff.extra, "only on server", Color::Cyan),
+            ];
+            if rows.iter().all(|(names, _, _)| names.is_empty()) {
+                println!(
+                    "{} {} environment variable(s) in sync",
+                    "✓".green().bold(),
+                    diff.in_sync.len()
+                );
+            } else {
+                let mut table = Table::new();
+                table.load_style(UTF8_FULL);
+                table.set_header(vec!["Name", "Status"]);
+                for (names, status, color) in rows {
+                    for name in names {
+                        table.add_row(vec![Cell::new(name), Cell::new(status).fg(color)]);
+                    }
+                }
+                println!("{}", table);
+                println!("\n{} in sync", diff.in_sync.len());
+            }
+        }
+    }
+
+    let unset = diff.unset();
+    if exit_code && !unset.is_empty() {
+        return Err(EnvVarsDrift { names: unset }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> BTreeSet<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn diff_sorts_names_into_one_bucket_each() {
+        let diff = diff_names(
+            &names(&["API_KEY", "DB_URL", "OLD_TOKEN"]),
+            &names(&["API_KEY", "DB_URL", "SENTRY_DSN", "REGION"]),
+            &names(&["REGION", "DB_URL"]),
+        );
+
+        assert_eq!(
+            diff,
+            EnvVarsDiff {
+                missing: vec!["SENTRY_DSN".to_string()],
+                declared_unset: vec!["REGION".to_string()],
+                extra: vec!["OLD_TOKEN".to_string()],
+                in_sync: vec!["API_KEY".to_string(), "DB_URL".to_string()],
+            }
+        );
+        assert_eq!(diff.unset(), vec!["REGION", "SENTRY_DSN"]);
+    }
+
+    #[test]
+    fn diff_with_nothing_local_only_reports_extra() {
+        let diff = diff_names(&names(&["A"]), &BTreeSet::new(), &BTreeSet::new());
+        assert_eq!(diff.extra, vec!["A"]);
+        assert!(diff.unset().is_empty());
+    }
+}
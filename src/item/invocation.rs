@@ -0,0 +1,261 @@
+use crate::{
+    OutputFormat,
+    client::RicochetClient,
+    config::Config,
+    item::invoke::{InvocationState, follow_invocation_logs},
+};
+use anyhow::Result;
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL};
+use indicatif::HumanDuration;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvocationRow {
+    #[serde(alias = "invocation_id")]
+    pub id: String,
+    pub content_id: Option<String>,
+    #[serde(default)]
+    pub status: String,
+    /// Unix timestamp in seconds
+    pub started_at: Option<i64>,
+    /// Unix timestamp in seconds, unset while running
+    pub ended_at: Option<i64>,
+    /// What started the invocation: `manual` or `schedule`
+    pub trigger: Option<String>,
+    pub exit_code: Option<i64>,
+}
+
+const DEFAULT_FIELDS: &[&str] = &["id", "status", "started_at", "duration", "trigger"];
+
+const ALL_FIELDS: &[&str] = &[
+    "id",
+    "content_id",
+    "status",
+    "started_at",
+    "ended_at",
+    "duration",
+    "trigger",
+    "exit_code",
+];
+
+fn status_cell(invocation: &InvocationRow) -> Cell {
+    let cell = Cell::new(&invocation.status);
+    match InvocationState::from_status(Some(&invocation.status)) {
+        InvocationState::Succeeded => cell.fg(Color::Green),
+        InvocationState::Failed => cell.fg(Color::Red),
+        InvocationState::Stopped => cell.fg(Color::Yellow),
+        InvocationState::Running => cell.fg(Color::Cyan),
+        InvocationState::Unknown => cell,
+    }
+}
+
+/// How long the invocation ran, or has been running so far.
+fn duration(invocation: &InvocationRow) -> Option<Duration> {
+    let started = invocation.started_at?;
+    let ended = invocation
+        .ended_at
+        .unwrap_or_else(|| chrono::Utc::now().timestamp());
+    u64::try_from(ended - started).ok().map(Duration::from_secs)
+}
+
+fn field_header(field: &str) -> &'static str {
+    match field {
+        "id" => "ID",
+        "content_id" => "Content ID",
+        "status" => "Status",
+        "started_at" => "Started At",
+        "ended_at" => "Ended At",
+        "duration" => "Duration",
+        "trigger" => "Trigger",
+        "exit_code" => "Exit Code",
+        _ => "Unknown",
+    }
+}
+
+fn field_cell(field: &str, i: &InvocationRow) -> Cell {
+    let time = |ts: Option<i64>| ts.map_or_else(|| "-".to_string(), super::format_unix_time);
+    match field {
+        "id" => Cell::new(&i.id),
+        "content_id" => Cell::new(i.content_id.as_deref().unwrap_or("-")),
+        "status" => status_cell(i),
+        "started_at" => Cell::new(time(i.started_at)),
+        "ended_at" => Cell::new(time(i.ended_at)),
+        "duration" => {
+            Cell::new(duration(i).map_or_else(|| "-".to_string(), |d| HumanDuration(d).to_string()))
+        }
+        "trigger" => Cell::new(i.trigger.as_deref().unwrap_or("-")),
+        "exit_code" => Cell::new(
+            i.exit_code
+                .map_or_else(|| "-".to_string(), |c| c.to_string()),
+        ),
+        _ => Cell::new("-"),
+    }
+}
+
+pub async fn list_invocations(
+    config: &Config,
+    server_ref: Option<&str>,
+    content_ulid: &str,
+    fields: Option<Vec<String>>,
+    format: OutputFormat,
+) -> Result<()> {
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let invocations = client.list_invocations(content_ulid).await?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&invocations)?);
+        }
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(&invocations)?);
+        }
+        OutputFormat::Table => {
+            println!("{}", server_config.url.as_str().italic().dimmed());
+
+            if invocations.is_empty() {
+                println!("{}", "No invocations found.".yellow());
+                return Ok(());
+            }
+
+            let cols = super::resolve_fields(fields, DEFAULT_FIELDS, ALL_FIELDS);
+
+            let mut table = Table::new();
+            table.load_style(UTF8_FULL);
+            table.set_header(cols.iter().map(|c| field_header(c)));
+
+            for i in &invocations {
+                table.add_row(cols.iter().map(|c| field_cell(c, i)));
+            }
+
+            println!("{}", table);
+            println!("\n{} invocations", invocations.len());
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn get_invocation(
+    config: &Config,
+    server_ref: Option<&str>,
+    content_ulid: &str,
+    invocation_id: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let i: InvocationRow =
+        serde_json::from_value(client.get_invocation(content_ulid, invocation_id).await?)?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&i)?);
+        }
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(&i)?);
+        }
+        OutputFormat::Table => {
+            println!("{}", server_config.url.as_str().italic().dimmed());
+
+            let mut table = Table::new();
+            table.load_style(UTF8_FULL);
+
+            for col in ALL_FIELDS {
+                table.add_row(vec![Cell::new(field_header(col)), field_cell(col, &i)]);
+            }
+
+            println!("{}", table);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn invocation_logs(
+    config: &Config,
+    server_ref: Option<&str>,
+    content_ulid: &str,
+    invocation_id: &str,
+    follow: bool,
+) -> Result<()> {
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    if follow {
+        let i = follow_invocation_logs(&client, content_ulid, invocation_id, None).await?;
+        eprintln!(
+            "\n{} Invocation {} finished with status {}",
+            "→".bright_cyan(),
+            invocation_id.bright_cyan(),
+            i.get("status")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+        );
+    } else {
+        print!(
+            "{}",
+            client
+                .get_invocation_logs(content_ulid, invocation_id)
+                .await?
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn stop_invocation(
+    config: &Config,
+    server_ref: Option<&str>,
+    content_ulid: &str,
+    invocation_id: &str,
+) -> Result<()> {
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    client.stop_invocation(content_ulid, invocation_id).await?;
+
+    println!(
+        "{} Invocation {} stopped",
+        "✓".green().bold(),
+        invocation_id.bright_cyan()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invocation(status: &str, exit_code: Option<i64>) -> InvocationRow {
+        InvocationRow {
+            id: "inv".to_string(),
+            content_id: None,
+            status: status.to_string(),
+            started_at: Some(100),
+            ended_at: Some(165),
+            trigger: None,
+            exit_code,
+        }
+    }
+
+    #[test]
+    fn duration_of_finished_invocation() {
+        let i = invocation("success", None);
+        assert_eq!(duration(&i), Some(Duration::from_secs(65)));
+        let not_started = InvocationRow {
+            started_at: None,
+            ..invocation("queued", None)
+        };
+        assert_eq!(duration(&not_started), None);
+    }
+}
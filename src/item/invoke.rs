@@ -1,12 +1,160 @@
-use crate::{OutputFormat, client::RicochetClient, config::Config};
-use anyhow::Result;
+use crate::{
+    OutputFormat,
+    client::RicochetClient,
+    config::Config,
+    item::deployment::{POLL_INTERVAL, unseen_output},
+};
+use anyhow::{Context, Result, bail};
 use colored::Colorize;
 use comfy_table::{Cell, Color, Table, presets::UTF8_FULL};
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
+/// Exit status for an invocation that was stopped before it finished, as if
+/// the CLI itself had been interrupted.
+pub const STOPPED_EXIT_CODE: i32 = 130;
+
+/// Where an invocation is in its lifecycle, judged from its `status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InvocationState {
+    Running,
+    Succeeded,
+    Failed,
+    Stopped,
+    /// The server sent no status, so there's no telling whether it ended
+    Unknown,
+}
+
+impl InvocationState {
+    fn of(invocation: &Value) -> Self {
+        Self::from_status(invocation.get("status").and_then(|v| v.as_str()))
+    }
+
+    pub(crate) fn from_status(status: Option<&str>) -> Self {
+        match status {
+            None | Some("") => Self::Unknown,
+            Some("pending" | "queued" | "starting" | "running") => Self::Running,
+            Some("success" | "succeeded" | "completed") => Self::Succeeded,
+            Some("stopped" | "cancelled" | "canceled" | "killed") => Self::Stopped,
+            Some(_) => Self::Failed,
+        }
+    }
+}
+
+/// An invocation that finished without succeeding. The CLI exits with `code`.
+#[derive(Debug)]
+pub struct InvocationFailed {
+    pub invocation_id: String,
+    pub status: String,
+    pub code: i32,
+}
+
+impl std::fmt::Display for InvocationFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invocation {} finished with status {}",
+            self.invocation_id, self.status
+        )
+    }
+}
+
+impl std::error::Error for InvocationFailed {}
+
+/// Build the parameters for a task invocation from `--params-file`,
+/// `--params-json` and `--param`, in increasing order of precedence: a key
+/// set by a later source replaces the same top-level key from an earlier one.
+/// Returns `None` when no parameters were given.
+pub fn resolve_params(
+    param: &[String],
+    params_json: Option<&str>,
+    params_file: Option<&Path>,
+) -> Result<Option<Map<String, Value>>> {
+    if param.is_empty() && params_json.is_none() && params_file.is_none() {
+        return Ok(None);
+    }
+
+    let mut params = Map::new();
+
+    if let Some(path) = params_file {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let value: Value = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            Some("yaml" | "yml") => serde_yaml::from_str(&content)?,
+            Some("toml") => toml::from_str(&content)?,
+            _ => bail!(
+                "Unsupported parameter file {}: expected a .json, .yaml, .yml or .toml file",
+                path.display()
+            ),
+        };
+        params.extend(into_object(value, &path.display().to_string())?);
+    }
+
+    if let Some(json) = params_json {
+        let value: Value = serde_json::from_str(json).context("--params-json is not valid JSON")?;
+        params.extend(into_object(value, "--params-json")?);
+    }
+
+    for entry in param {
+        let Some((key, value)) = entry.split_once('=') else {
+            bail!("Invalid --param '{entry}': expected KEY=VALUE");
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("Invalid --param '{entry}': the key is empty");
+        }
+        params.insert(key.to_string(), coerce_param(value));
+    }
+
+    Ok(Some(params))
+}
+
+/// Parameters must be a map of names to values.
+fn into_object(value: Value, source: &str) -> Result<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Ok(map),
+        other => bail!(
+            "{source} must contain an object of parameter names to values, not {}",
+            match other {
+                Value::Null => "null",
+                Value::Bool(_) => "a boolean",
+                Value::Number(_) => "a number",
+                Value::String(_) => "a string",
+                Value::Array(_) => "an array",
+                Value::Object(_) => unreachable!(),
+            }
+        ),
+    }
+}
+
+/// Interpret a `--param` value: JSON literals (`true`, `3`, `1.5`, `null`,
+/// `[1, 2]`, `{"a": 1}`, `"quoted"`) keep their type, anything else is a string.
+/// A number that wouldn't be sent back as written (`1.10`, `1e3`, or an
+/// integer too large to hold exactly) stays a string, so versions and codes
+/// arrive intact.
+fn coerce_param(value: &str) -> Value {
+    match serde_json::from_str(value) {
+        Ok(Value::Number(n)) if n.to_string() != value.trim() => Value::String(value.to_string()),
+        Ok(parsed) => parsed,
+        Err(_) => Value::String(value.to_string()),
+    }
+}
+
+/// Invoke a task. With `wait`, `follow` or a `timeout`, wait for the
+/// invocation to end and fail with [`InvocationFailed`] unless it succeeds.
+#[allow(clippy::too_many_arguments)]
 pub async fn invoke(
     config: &Config,
     server_ref: Option<&str>,
     id: &str,
+    params: Option<Map<String, Value>>,
+    wait: bool,
+    follow: bool,
+    timeout: Option<Duration>,
     format: OutputFormat,
 ) -> Result<()> {
     eprintln!("Invoking task: {}", id.bright_cyan());
@@ -16,7 +164,8 @@ pub async fn invoke(
     let client = RicochetClient::new(&server_config)?;
     client.preflight_key_check().await?;
 
-    match client.invoke(id, None).await {
+    let params = params.map(|p| Value::Object(p).to_string());
+    match client.invoke(id, params).await {
         Ok(result) => {
             eprintln!("{} Task invoked successfully!\n", "✓".green().bold());
 
@@ -61,6 +210,22 @@ pub async fn invoke(
                 }
             }
 
+            if wait || follow || timeout.is_some() {
+                let Some(invocation_id) = result.get("invocation_id").and_then(|v| v.as_str())
+                else {
+                    bail!(
+                        "The server did not return an invocation ID, so there is nothing to wait for"
+                    );
+                };
+                let started = Instant::now();
+                let finished = if follow {
+                    follow_invocation_logs(&client, id, invocation_id, timeout).await?
+                } else {
+                    wait_for_invocation(&client, id, invocation_id, timeout).await?
+                };
+                report_invocation(invocation_id, &finished, started.elapsed())?;
+            }
+
             Ok(())
         }
         Err(e) => {
@@ -68,3 +233,319 @@ pub async fn invoke(
         }
     }
 }
+
+/// Whether `invocation` has ended. Fails if the server didn't say, rather
+/// than waiting on a status that may never come.
+fn has_ended(invocation_id: &str, invocation: &Value) -> Result<bool> {
+    match InvocationState::of(invocation) {
+        InvocationState::Running => Ok(false),
+        InvocationState::Unknown => bail!(
+            "The server reported no status for invocation {}, so there is no telling when it ends",
+            invocation_id
+        ),
+        _ => Ok(true),
+    }
+}
+
+/// Fail once `timeout` has passed since `started`. The invocation itself is
+/// left running.
+fn check_timeout(invocation_id: &str, started: Instant, timeout: Option<Duration>) -> Result<()> {
+    if let Some(timeout) = timeout
+        && started.elapsed() >= timeout
+    {
+        bail!(
+            "Invocation {} is still running after {}; stop it with `ricochet task invocation stop`",
+            invocation_id,
+            HumanDuration(timeout)
+        );
+    }
+    Ok(())
+}
+
+/// Poll an invocation until it ends, showing a spinner with the elapsed
+/// time. Returns the invocation in its final state, or fails after `timeout`.
+async fn wait_for_invocation(
+    client: &RicochetClient,
+    id: &str,
+    invocation_id: &str,
+    timeout: Option<Duration>,
+) -> Result<Value> {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg} [{elapsed}]")?);
+    pb.enable_steady_tick(Duration::from_millis(80));
+    pb.set_message(format!(
+        "Waiting for invocation {} to finish",
+        invocation_id.bright_cyan()
+    ));
+
+    let started = Instant::now();
+    let result = async {
+        loop {
+            let invocation = client.get_invocation(id, invocation_id).await?;
+            if has_ended(invocation_id, &invocation)? {
+                return Ok(invocation);
+            }
+            check_timeout(invocation_id, started, timeout)?;
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+    .await;
+    pb.finish_and_clear();
+    result
+}
+
+/// Print an invocation's output as it grows until the invocation ends.
+/// Returns the invocation in its final state, or fails after `timeout`.
+pub(crate) async fn follow_invocation_logs(
+    client: &RicochetClient,
+    id: &str,
+    invocation_id: &str,
+    timeout: Option<Duration>,
+) -> Result<Value> {
+    let started = Instant::now();
+    let mut printed = 0;
+    loop {
+        // Read the status before the log so the last fetch holds everything
+        // written before the invocation ended
+        let invocation = client.get_invocation(id, invocation_id).await?;
+        let log = client.get_invocation_logs(id, invocation_id).await?;
+        print!("{}", unseen_output(&log, printed));
+        std::io::stdout().flush()?;
+        printed = log.len();
+
+        if has_ended(invocation_id, &invocation)? {
+            return Ok(invocation);
+        }
+        check_timeout(invocation_id, started, timeout)?;
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Print how a finished invocation ended, failing with [`InvocationFailed`]
+/// unless it succeeded. A failed task's own exit code is passed on when the
+/// server reports one that a process can exit with and still read as failed
+/// (1-255).
+fn report_invocation(invocation_id: &str, invocation: &Value, elapsed: Duration) -> Result<()> {
+    let status = invocation
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    let code = match InvocationState::of(invocation) {
+        InvocationState::Running => {
+            bail!(
+                "Invocation {} is still {}, so it has no outcome to report",
+                invocation_id,
+                status
+            )
+        }
+        InvocationState::Succeeded => {
+            eprintln!(
+                "{} Invocation {} succeeded after {}",
+                "✓".green().bold(),
+                invocation_id.bright_cyan(),
+                HumanDuration(elapsed)
+            );
+            return Ok(());
+        }
+        InvocationState::Stopped => STOPPED_EXIT_CODE,
+        InvocationState::Failed | InvocationState::Unknown => invocation
+            .get("exit_code")
+            .and_then(|v| v.as_i64())
+            .and_then(|code| i32::try_from(code).ok())
+            .filter(|code| (1..=255).contains(code))
+            .unwrap_or(1),
+    };
+
+    eprintln!(
+        "{} Invocation {} finished with status {} after {}",
+        "✗".red().bold(),
+        invocation_id.bright_cyan(),
+        status.red(),
+        HumanDuration(elapsed)
+    );
+    Err(InvocationFailed {
+        invocation_id: invocation_id.to_string(),
+        status: status.to_string(),
+        code,
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params(param: &[&str]) -> Vec<String> {
+        param.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn invocation_state_from_status() {
+        assert_eq!(InvocationState::of(&json!({})), InvocationState::Unknown);
+        let invocation = json!({"status": ""});
+        assert_eq!(InvocationState::of(&invocation), InvocationState::Unknown);
+        for status in ["queued", "running"] {
+            let invocation = json!({ "status": status });
+            assert_eq!(InvocationState::of(&invocation), InvocationState::Running);
+        }
+        let invocation = json!({"status": "completed"});
+        assert_eq!(InvocationState::of(&invocation), InvocationState::Succeeded);
+        let invocation = json!({"status": "stopped"});
+        assert_eq!(InvocationState::of(&invocation), InvocationState::Stopped);
+        for status in ["failed", "error", "timeout"] {
+            let invocation = json!({ "status": status });
+            assert_eq!(InvocationState::of(&invocation), InvocationState::Failed);
+        }
+    }
+
+    fn exit_code(invocation: Value) -> Option<i32> {
+        report_invocation("inv", &invocation, Duration::ZERO)
+            .err()
+            .map(|e| e.downcast_ref::<InvocationFailed>().unwrap().code)
+    }
+
+    #[test]
+    fn report_invocation_exit_codes() {
+        assert_eq!(exit_code(json!({"status": "success"})), None);
+        assert_eq!(exit_code(json!({"status": "failed"})), Some(1));
+        assert_eq!(
+            exit_code(json!({"status": "failed", "exit_code": 3})),
+            Some(3)
+        );
+        assert_eq!(
+            exit_code(json!({"status": "failed", "exit_code": 0})),
+            Some(1)
+        );
+        assert_eq!(
+            exit_code(json!({"status": "stopped"})),
+            Some(STOPPED_EXIT_CODE)
+        );
+        // Codes a process can't exit with would wrap, some of them to 0
+        for code in [256, 512, -1, i64::MAX] {
+            assert_eq!(
+                exit_code(json!({"status": "failed", "exit_code": code})),
+                Some(1)
+            );
+        }
+    }
+
+    #[test]
+    fn report_invocation_refuses_a_running_invocation() {
+        let err =
+            report_invocation("inv", &json!({"status": "running"}), Duration::ZERO).unwrap_err();
+        assert!(err.downcast_ref::<InvocationFailed>().is_none());
+        assert!(err.to_string().contains("still running"), "{err}");
+    }
+
+    #[test]
+    fn no_params_sends_none() {
+        assert!(resolve_params(&[], None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn numbers_that_would_change_stay_strings() {
+        let resolved = resolve_params(
+            &params(&[
+                "version=1.10",
+                "zip=01234",
+                "big=123456789012345678901234567890",
+                "scale=1e3",
+                "n=-7",
+            ]),
+            None,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            Value::Object(resolved),
+            json!({
+                "version": "1.10",
+                "zip": "01234",
+                "big": "123456789012345678901234567890",
+                "scale": "1e3",
+                "n": -7,
+            })
+        );
+    }
+
+    #[test]
+    fn param_values_are_coerced() {
+        let resolved = resolve_params(
+            &params(&[
+                "n=10",
+                "ratio=0.5",
+                "verbose=true",
+                "skip=null",
+                "ids=[1, 2]",
+                "name=report",
+                "year=\"2024\"",
+                "query=a=b",
+            ]),
+            None,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            Value::Object(resolved),
+            json!({
+                "n": 10,
+                "ratio": 0.5,
+                "verbose": true,
+                "skip": null,
+                "ids": [1, 2],
+                "name": "report",
+                "year": "2024",
+                "query": "a=b",
+            })
+        );
+    }
+
+    #[test]
+    fn later_sources_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("params.yaml");
+        std::fs::write(&file, "a: file\nb: file\nc: file\n").unwrap();
+
+        let resolved = resolve_params(
+            &params(&["c=flag"]),
+            Some(r#"{"b": "json", "c": "json"}"#),
+            Some(&file),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            Value::Object(resolved),
+            json!({"a": "file", "b": "json", "c": "flag"})
+        );
+    }
+
+    #[test]
+    fn params_file_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml = dir.path().join("params.toml");
+        std::fs::write(&toml, "n = 3\n[opts]\nfast = true\n").unwrap();
+        let json = dir.path().join("params.json");
+        std::fs::write(&json, r#"{"n": 3, "opts": {"fast": true}}"#).unwrap();
+
+        let expected = json!({"n": 3, "opts": {"fast": true}});
+        for file in [&toml, &json] {
+            let resolved = resolve_params(&[], None, Some(file)).unwrap().unwrap();
+            assert_eq!(Value::Object(resolved), expected);
+        }
+    }
+
+    #[test]
+    fn invalid_params_are_rejected() {
+        assert!(resolve_params(&params(&["novalue"]), None, None).is_err());
+        assert!(resolve_params(&params(&["=1"]), None, None).is_err());
+        assert!(resolve_params(&[], Some("[1, 2]"), None).is_err());
+        assert!(resolve_params(&[], Some("{not json"), None).is_err());
+        assert!(resolve_params(&[], None, Some(Path::new("params.txt"))).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod deployment;
 pub mod env_vars;
+pub mod invocation;
 pub mod invoke;
 pub mod schedule;
 pub mod settings;
@@ -50,6 +51,31 @@ pub fn resolve_id(id: Option<&str>, path: Option<&Path>) -> Result<String> {
     }
 }
 
+/// Pick the table columns to show for `--fields`: `default` when unset, every
+/// column in `all` for `all`, otherwise the named columns in `all` order.
+pub(crate) fn resolve_fields(
+    fields: Option<Vec<String>>,
+    default: &[&'static str],
+    all: &[&'static str],
+) -> Vec<&'static str> {
+    match fields {
+        None => default.to_vec(),
+        Some(f) if f.len() == 1 && f[0] == "all" => all.to_vec(),
+        Some(f) => all
+            .iter()
+            .copied()
+            .filter(|col| f.iter().any(|s| s == col))
+            .collect(),
+    }
+}
+
+/// Format a Unix timestamp in seconds as a UTC date and time.
+pub(crate) fn format_unix_time(ts: i64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
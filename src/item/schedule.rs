@@ -1,17 +1,344 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use colored::Colorize;
+use comfy_table::{Table, presets::UTF8_FULL};
+use jiff::Timestamp;
+use jiff::tz::{AmbiguousOffset, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::str::FromStr;
 
-use crate::{OutputFormat, client::RicochetClient, config::Config};
+use crate::{OutputFormat, client::RicochetClient, config::Config, item::resolve_id};
+
+/// A task's schedule as stored on the server.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Cron expression, unset when the task has no schedule
+    pub schedule: Option<String>,
+    /// A paused schedule keeps its expression but does not fire
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// What `task schedule show` reports.
+#[derive(Debug, Serialize)]
+pub struct ScheduleReport {
+    pub id: String,
+    pub schedule: Option<String>,
+    pub paused: bool,
+    /// Plain-English reading of the cron expression
+    pub description: Option<String>,
+    /// Upcoming times the schedule fires, in UTC, ignoring `paused`
+    pub next_runs: Vec<DateTime<Utc>>,
+    /// `next_runs` converted to the zone given with `--tz`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub next_runs_local: Vec<jiff::Zoned>,
+}
+
+/// What `task schedule preview` reports.
+#[derive(Debug, Serialize)]
+pub struct PreviewReport {
+    pub schedule: String,
+    pub description: String,
+    /// Zone the runs are also shown in. The cron fields are read in UTC, as
+    /// the server does.
+    pub timezone: String,
+    pub runs: Vec<PreviewRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreviewRun {
+    pub local: jiff::Zoned,
+    pub utc: DateTime<Utc>,
+    /// Set when daylight saving time affects how the run reads in the zone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dst: Option<DstTransition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DstTransition {
+    /// The zone's offset changed since the previous run, so this run falls
+    /// at a different local time
+    Shifted,
+    /// The clocks pass this local time twice; the offset tells which pass
+    Ambiguous,
+}
+
+/// Look up an IANA time zone name such as `Europe/Berlin`.
+pub fn parse_tz(name: &str) -> Result<TimeZone> {
+    TimeZone::get(name).with_context(|| {
+        format!("Unknown time zone '{name}', expected an IANA name like Europe/Berlin")
+    })
+}
+
+fn to_zoned(time: DateTime<Utc>, tz: &TimeZone) -> Result<jiff::Zoned> {
+    Ok(Timestamp::from_second(time.timestamp())?.to_zoned(tz.clone()))
+}
+
+/// List the next `count` times `expr` fires after `now`, evaluated in UTC like
+/// [`report`] and the server, and show each one in `tz`.
+fn preview(expr: &str, tz_name: &str, count: usize, now: DateTime<Utc>) -> Result<PreviewReport> {
+    let tz = parse_tz(tz_name)?;
+    let cron = croner::Cron::from_str(expr).context("parsing cron schedule")?;
+
+    let mut offset = to_zoned(now, &tz)?.offset();
+    let mut runs = Vec::with_capacity(count);
+    for utc in cron.iter_after(now).take(count) {
+        let local = to_zoned(utc, &tz)?;
+        let dst = match tz.to_ambiguous_zoned(local.datetime()).offset() {
+            AmbiguousOffset::Fold { .. } => Some(DstTransition::Ambiguous),
+            _ if local.offset() != offset => Some(DstTransition::Shifted),
+            _ => None,
+        };
+        offset = local.offset();
+        runs.push(PreviewRun { local, utc, dst });
+    }
+
+    Ok(PreviewReport {
+        schedule: expr.to_string(),
+        description: cron.describe(),
+        timezone: tz_name.to_string(),
+        runs,
+    })
+}
+
+/// Show when a cron expression fires, without contacting a server.
+pub fn preview_schedule(
+    schedule: &str,
+    count: usize,
+    tz: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let report = preview(schedule, tz.unwrap_or("UTC"), count, Utc::now())?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(&report)?);
+        }
+        OutputFormat::Table => {
+            println!("  {:<12} {}", "Schedule:".dimmed(), report.schedule);
+            println!("  {:<12} {}", "Runs:".dimmed(), report.description);
+            println!("  {:<12} {}", "Time zone:".dimmed(), report.timezone);
+            println!();
+
+            let mut table = Table::new();
+            table.load_style(UTF8_FULL);
+            table.set_header(vec![report.timezone.as_str(), "UTC", "Note"]);
+            for run in &report.runs {
+                let note = match run.dst {
+                    None => String::new(),
+                    Some(DstTransition::Shifted) => {
+                        "⚠ local time moved by a DST change".to_string()
+                    }
+                    Some(DstTransition::Ambiguous) => {
+                        "⚠ ambiguous, clocks pass this time twice".to_string()
+                    }
+                };
+                table.add_row(vec![
+                    run.local.strftime("%Y-%m-%d %H:%M:%S %Z").to_string(),
+                    run.utc.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    note,
+                ]);
+            }
+            println!("{table}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Describe `schedule` and list the next `count` times it fires after `now`.
+fn report(
+    id: &str,
+    schedule: Schedule,
+    count: usize,
+    now: DateTime<Utc>,
+    tz: Option<&TimeZone>,
+) -> Result<ScheduleReport> {
+    let (description, next_runs) = match &schedule.schedule {
+        Some(expr) => {
+            let cron = croner::Cron::from_str(expr).context("parsing cron schedule")?;
+            let next_runs = cron.iter_after(now).take(count).collect();
+            (Some(cron.describe()), next_runs)
+        }
+        None => (None, Vec::new()),
+    };
+    let next_runs_local = match tz {
+        Some(tz) => next_runs
+            .iter()
+            .map(|run| to_zoned(*run, tz))
+            .collect::<Result<_>>()?,
+        None => Vec::new(),
+    };
+    Ok(ScheduleReport {
+        id: id.to_string(),
+        schedule: schedule.schedule,
+        paused: schedule.paused,
+        description,
+        next_runs,
+        next_runs_local,
+    })
+}
+
+pub async fn show_schedule(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    count: usize,
+    tz: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let tz = tz.map(parse_tz).transpose()?;
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let schedule = client.get_schedule(&id).await?;
+    let report = report(&id, schedule, count, Utc::now(), tz.as_ref())?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(&report)?);
+        }
+        OutputFormat::Table => {
+            println!("{}", server_config.url.as_str().italic().dimmed());
+
+            let Some(expr) = &report.schedule else {
+                println!("{}", "No schedule set.".yellow());
+                return Ok(());
+            };
+
+            let status = if report.paused {
+                "paused".yellow()
+            } else {
+                "active".green()
+            };
+            println!("  {:<12} {}", "Schedule:".dimmed(), expr);
+            if let Some(description) = &report.description {
+                println!("  {:<12} {}", "Runs:".dimmed(), description);
+            }
+            println!("  {:<12} {}", "Status:".dimmed(), status);
+
+            if !report.next_runs.is_empty() {
+                let label = if report.paused {
+                    "Next runs once resumed:"
+                } else {
+                    "Next runs:"
+                };
+                println!("\n  {}", label.dimmed());
+                for (i, run) in report.next_runs.iter().enumerate() {
+                    match report.next_runs_local.get(i) {
+                        Some(local) => println!(
+                            "    {} UTC  {}",
+                            run.format("%Y-%m-%d %H:%M:%S"),
+                            local
+                                .strftime("(%Y-%m-%d %H:%M:%S %Z)")
+                                .to_string()
+                                .dimmed()
+                        ),
+                        None => println!("    {} UTC", run.format("%Y-%m-%d %H:%M:%S")),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn clear_schedule(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    client.clear_schedule(&id).await?;
+
+    println!(
+        "{} Schedule removed from {}",
+        "✓".green().bold(),
+        id.bright_cyan()
+    );
+
+    Ok(())
+}
+
+/// Pause or resume a task's schedule without changing its expression.
+pub async fn set_schedule_paused(
+    config: &Config,
+    server_ref: Option<&str>,
+    id: Option<&str>,
+    path: Option<&Path>,
+    paused: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let id = resolve_id(id, path)?;
+    let server_config = config.resolve_server(server_ref)?;
+    let client = RicochetClient::new(&server_config)?;
+    client.preflight_key_check().await?;
+
+    let schedule = client.set_schedule_paused(&id, paused).await?;
+
+    match format {
+        OutputFormat::Table => {
+            let action = if paused { "paused" } else { "resumed" };
+            println!(
+                "{} Schedule for {} {}",
+                "✓".green().bold(),
+                id.bright_cyan(),
+                action
+            );
+            if let Some(expr) = &schedule.schedule {
+                println!("  {:<12} {}", "Schedule:".dimmed(), expr);
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&schedule)?);
+        }
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(&schedule)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Sort out `task schedule [ID] [SCHEDULE]` into an ID and a cron expression.
+/// A lone positional is the expression if it parses as cron, otherwise the ID
+/// of the item whose schedule to show.
+pub fn split_schedule_args(
+    id: Option<String>,
+    schedule: Option<String>,
+) -> (Option<String>, Option<String>) {
+    match (id, schedule) {
+        (Some(arg), None) if croner::Cron::from_str(&arg).is_ok() => (None, Some(arg)),
+        args => args,
+    }
+}
 
 pub async fn schedule_task(
     config: &Config,
     server_ref: Option<&str>,
     id: &str,
     schedule: &str,
+    tz: Option<&str>,
     format: OutputFormat,
 ) -> Result<()> {
+    let tz = tz.map(parse_tz).transpose()?;
     // validate the cron schedule locally before hitting the API
     let cron = croner::Cron::from_str(schedule).context("parsing cron schedule")?;
     let next = cron
@@ -38,6 +365,13 @@ pub async fn schedule_task(
                 "Next run:".dimmed(),
                 next.format("%Y-%m-%d %H:%M:%S")
             );
+            if let Some(tz) = &tz {
+                println!(
+                    "  {:<12} {}",
+                    "",
+                    to_zoned(next, tz)?.strftime("%Y-%m-%d %H:%M:%S %Z")
+                );
+            }
         }
         OutputFormat::Json => {
             println!("{}", serde_json::to_string_pretty(&res)?);
@@ -49,3 +383,183 @@ pub async fn schedule_task(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_upcoming_runs() {
+        let now = DateTime::parse_from_rfc3339("2025-01-06T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let schedule = Schedule {
+            schedule: Some("0 9 * * 1-5".to_string()),
+            paused: true,
+        };
+
+        let report = report("id", schedule, 3, now, None).unwrap();
+
+        let runs: Vec<String> = report.next_runs.iter().map(|r| r.to_rfc3339()).collect();
+        assert_eq!(
+            runs,
+            vec![
+                "2025-01-06T09:00:00+00:00",
+                "2025-01-07T09:00:00+00:00",
+                "2025-01-08T09:00:00+00:00",
+            ]
+        );
+        assert!(report.paused);
+        assert!(report.description.is_some());
+    }
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn preview_reads_the_expression_in_utc() {
+        let now = utc("2025-01-06T08:00:00Z");
+
+        let report = preview("0 9 * * *", "Europe/Berlin", 1, now).unwrap();
+
+        // The server fires at 09:00 UTC, which is 10:00 in Berlin
+        assert_eq!(report.runs[0].utc.to_rfc3339(), "2025-01-06T09:00:00+00:00");
+        assert_eq!(
+            report.runs[0].local.to_string(),
+            "2025-01-06T10:00:00+01:00[Europe/Berlin]"
+        );
+    }
+
+    #[test]
+    fn preview_agrees_with_show() {
+        let now = utc("2025-03-28T12:00:00Z");
+        let tz = parse_tz("Europe/Berlin").unwrap();
+        let schedule = Schedule {
+            schedule: Some("0 9 * * *".to_string()),
+            paused: false,
+        };
+
+        let preview = preview("0 9 * * *", "Europe/Berlin", 5, now).unwrap();
+        let report = report("id", schedule, 5, now, Some(&tz)).unwrap();
+
+        let preview_utc: Vec<_> = preview.runs.iter().map(|r| r.utc).collect();
+        let preview_local: Vec<_> = preview.runs.iter().map(|r| r.local.clone()).collect();
+        assert_eq!(preview_utc, report.next_runs);
+        assert_eq!(preview_local, report.next_runs_local);
+    }
+
+    #[test]
+    fn preview_flags_runs_shifted_by_spring_forward() {
+        let now = utc("2025-03-28T12:00:00Z");
+
+        let report = preview("30 1 * * *", "Europe/Berlin", 3, now).unwrap();
+
+        let runs: Vec<(String, Option<DstTransition>)> = report
+            .runs
+            .iter()
+            .map(|r| (r.local.to_string(), r.dst))
+            .collect();
+        assert_eq!(
+            runs,
+            vec![
+                ("2025-03-29T02:30:00+01:00[Europe/Berlin]".to_string(), None),
+                (
+                    "2025-03-30T03:30:00+02:00[Europe/Berlin]".to_string(),
+                    Some(DstTransition::Shifted)
+                ),
+                ("2025-03-31T03:30:00+02:00[Europe/Berlin]".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn preview_flags_ambiguous_fall_back_time() {
+        let now = utc("2025-10-25T12:00:00Z");
+
+        let report = preview("30 0 * * *", "Europe/Berlin", 2, now).unwrap();
+
+        assert_eq!(
+            report.runs[0].local.to_string(),
+            "2025-10-26T02:30:00+02:00[Europe/Berlin]"
+        );
+        assert_eq!(report.runs[0].dst, Some(DstTransition::Ambiguous));
+        assert_eq!(
+            report.runs[1].local.to_string(),
+            "2025-10-27T01:30:00+01:00[Europe/Berlin]"
+        );
+        assert_eq!(report.runs[1].dst, Some(DstTransition::Shifted));
+    }
+
+    #[test]
+    fn preview_defaults_to_utc() {
+        let now = utc("2025-01-06T08:00:00Z");
+
+        let report = preview("0 9 * * 1-5", "UTC", 1, now).unwrap();
+
+        assert_eq!(report.runs[0].utc.to_rfc3339(), "2025-01-06T09:00:00+00:00");
+        assert_eq!(report.runs[0].dst, None);
+    }
+
+    #[test]
+    fn lone_argument_is_a_cron_expression_or_an_id() {
+        assert_eq!(
+            split_schedule_args(Some("0 9 * * 1-5".to_string()), None),
+            (None, Some("0 9 * * 1-5".to_string()))
+        );
+        assert_eq!(
+            split_schedule_args(Some("01JSZAXZ3TSTAYXP56ARDVFJCJ".to_string()), None),
+            (Some("01JSZAXZ3TSTAYXP56ARDVFJCJ".to_string()), None)
+        );
+        assert_eq!(
+            split_schedule_args(
+                Some("01JSZAXZ3TSTAYXP56ARDVFJCJ".to_string()),
+                Some("0 9 * * *".to_string())
+            ),
+            (
+                Some("01JSZAXZ3TSTAYXP56ARDVFJCJ".to_string()),
+                Some("0 9 * * *".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn preview_rejects_unknown_zone() {
+        let err = preview("0 9 * * *", "Mars/Olympus", 1, Utc::now()).unwrap_err();
+        assert!(err.to_string().contains("Mars/Olympus"));
+    }
+
+    #[test]
+    fn report_converts_runs_to_zone() {
+        let now = DateTime::parse_from_rfc3339("2025-01-06T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let schedule = Schedule {
+            schedule: Some("0 9 * * *".to_string()),
+            paused: false,
+        };
+        let tz = parse_tz("Europe/Berlin").unwrap();
+
+        let report = report("id", schedule, 1, now, Some(&tz)).unwrap();
+
+        assert_eq!(
+            report.next_runs_local[0].to_string(),
+            "2025-01-06T10:00:00+01:00[Europe/Berlin]"
+        );
+    }
+
+    #[test]
+    fn report_without_schedule() {
+        let schedule = Schedule {
+            schedule: None,
+            paused: false,
+        };
+
+        let report = report("id", schedule, 3, Utc::now(), None).unwrap();
+
+        assert!(report.next_runs.is_empty());
+        assert!(report.description.is_none());
+    }
+}
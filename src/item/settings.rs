@@ -21,6 +21,11 @@ fn format_changes(id: &str, name: &str, changes: &[FieldChange]) -> String {
         id.bright_cyan(),
         name
     );
+    format!("{header}\n{}", format_change_rows(changes))
+}
+
+/// Render one padded `field  from → to` row per change.
+pub(crate) fn format_change_rows(changes: &[FieldChange]) -> String {
     let width = changes.iter().map(|c| c.field.len()).max().unwrap_or(0);
     let rows: Vec<String> = changes
         .iter()
@@ -35,7 +40,7 @@ fn format_changes(id: &str, name: &str, changes: &[FieldChange]) -> String {
             )
         })
         .collect();
-    format!("{header}\n{}", rows.join("\n"))
+    rows.join("\n")
 }
 
 /// Fetch the remote item and diff it against the local one.
@@ -71,13 +76,13 @@ fn render(v: &Value) -> String {
     }
 }
 
-struct PatchBuilder {
+pub(crate) struct PatchBuilder {
     changes: Vec<FieldChange>,
     patch: Map<String, Value>,
 }
 
 impl PatchBuilder {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             changes: Vec::new(),
             patch: Map::new(),
@@ -85,7 +90,7 @@ impl PatchBuilder {
     }
 
     /// Diff a single field within a group; always considered.
-    fn field(&mut self, group: &str, name: &str, remote: Value, local: Value) {
+    pub(crate) fn field(&mut self, group: &str, name: &str, remote: Value, local: Value) {
         if remote != local {
             self.changes.push(FieldChange {
                 field: format!("{group}.{name}"),
@@ -122,7 +127,7 @@ impl PatchBuilder {
         }
     }
 
-    fn finish(self) -> (Vec<FieldChange>, Value) {
+    pub(crate) fn finish(self) -> (Vec<FieldChange>, Value) {
         (self.changes, Value::Object(self.patch))
     }
 }
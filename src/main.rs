@@ -1,7 +1,7 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use ricochet_cli::{OutputFormat, app, commands, config::Config, item, update};
+use ricochet_cli::{OutputFormat, app, commands, config::Config, env_vars, item, update};
 
 // App specific methods go in `src/app/`
 // Task specific methods go in `src/task`
@@ -71,6 +71,24 @@ enum Commands {
         /// .env, .Renviron, or the calling environment. Repeatable.
         #[arg(short = 'e', long = "env", value_name = "KEY[=VALUE]")]
         env: Vec<String>,
+        /// Set every variable in this dotenv file on the initial deployment.
+        /// Repeatable; later files win
+        #[arg(long, value_name = "PATH", conflicts_with = "git")]
+        from_file: Vec<std::path::PathBuf>,
+        /// Only take names from --from-file matching this glob. Repeatable
+        #[arg(long, value_name = "GLOB", requires = "from_file")]
+        only: Vec<String>,
+        /// Skip names from --from-file matching this glob. Repeatable
+        #[arg(long, value_name = "GLOB", requires = "from_file")]
+        except: Vec<String>,
+        /// Send the variables from --from-file without asking. Required when
+        /// not running in a terminal
+        #[arg(short = 'y', long, requires = "from_file")]
+        yes: bool,
+        /// Encrypt environment variables even if the server isn't configured,
+        /// so its key can't be checked against a pinned one
+        #[arg(long)]
+        trust_unpinned_key: bool,
         /// Deploy from a Git repository instead of a local bundle
         #[arg(long)]
         git: Option<String>,
@@ -86,6 +104,54 @@ enum Commands {
         /// Git credential ID to use for private repos (only with --git)
         #[arg(long, requires = "git")]
         credential: Option<String>,
+        /// Wait for the deployment to finish and exit non-zero unless it succeeds
+        #[arg(long, conflicts_with = "git")]
+        wait: bool,
+        /// Stream the build log until the deployment finishes (implies --wait)
+        #[arg(long, conflicts_with = "git")]
+        logs: bool,
+        /// Show the files that would be bundled without contacting the server.
+        /// Anything under `.ricochet/` is ricochet's local state and is never
+        /// bundled; it is listed among the skipped files
+        #[arg(long, conflicts_with_all = ["git", "wait", "logs", "env", "from_file"])]
+        dry_run: bool,
+        /// Upload a bundle built with `ricochet bundle` instead of bundling PATH
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["git", "dry_run"])]
+        bundle: Option<std::path::PathBuf>,
+        /// Also skip files matched by .gitignore files (.ricochetignore is always honoured)
+        #[arg(long, conflicts_with_all = ["git", "bundle"])]
+        gitignore: bool,
+        /// Deploy even if the last deployment from here uploaded the same bundle
+        #[arg(long, conflicts_with_all = ["git", "dry_run"])]
+        force: bool,
+        /// List the files changed since the last deployment from here before uploading
+        #[arg(long, conflicts_with_all = ["git", "dry_run", "bundle"])]
+        diff: bool,
+        /// After the deployment succeeds, request this path from the app until it
+        /// answers with --expect-status (implies --wait). Exits with status 3 if it never does
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["git", "dry_run"])]
+        check_path: Option<String>,
+        /// Status the smoke check expects from --check-path
+        #[arg(long, default_value_t = 200, requires = "check_path")]
+        expect_status: u16,
+        /// How long the smoke check may take (e.g. 90s, 2m)
+        #[arg(long, default_value = "120s", value_parser = ricochet_cli::utils::parse_duration, requires = "check_path")]
+        timeout: std::time::Duration,
+        /// Roll back to the previous successful deployment if the smoke check fails
+        #[arg(long, requires = "check_path")]
+        rollback_on_failure: bool,
+    },
+    /// Build a deployment bundle without uploading it
+    Bundle {
+        /// Path to the content directory
+        #[arg(default_value = ".")]
+        path: std::path::PathBuf,
+        /// Where to write the gzipped tarball
+        #[arg(short = 'o', long)]
+        output: std::path::PathBuf,
+        /// Also skip files matched by .gitignore files (.ricochetignore is always honoured)
+        #[arg(long)]
+        gitignore: bool,
     },
     /// Delete a content item
     Delete {
@@ -191,6 +257,78 @@ enum ItemCommands {
         #[arg(short = 'p', long)]
         path: Option<std::path::PathBuf>,
     },
+    /// Show the logs of running instances, prefixed with the instance ID
+    Logs {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Only show logs from this instance
+        #[arg(short = 'i', long)]
+        instance: Option<String>,
+        /// Keep printing new lines as they are written
+        #[arg(short = 'f', long)]
+        follow: bool,
+        /// Only show lines written since a duration ago (e.g. 10m, 1h30m) or a timestamp
+        #[arg(long)]
+        since: Option<String>,
+        /// Only show lines matching this regular expression
+        #[arg(short = 'g', long)]
+        grep: Option<String>,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Change the number of instances an app runs, without applying other local settings
+    Scale {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Minimum number of instances to keep running
+        #[arg(long = "min")]
+        min_instances: Option<u32>,
+        /// Maximum number of instances to run
+        #[arg(long = "max")]
+        max_instances: Option<u32>,
+        /// Connection load at which another instance is spawned
+        #[arg(long)]
+        spawn_threshold: Option<u32>,
+        /// Skip the confirmation prompts. The local _ricochet.toml is then
+        /// left alone unless --write-toml is given
+        #[arg(short = 'f', long)]
+        force: bool,
+        /// Save the new values to the local _ricochet.toml without asking
+        #[arg(long)]
+        write_toml: bool,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Serve an app on a local port, adding your API key to every request
+    Proxy {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Local port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Restart all instances, optionally a few at a time to avoid downtime
+    Restart {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Restart in batches, waiting for each batch to be replaced before the next
+        #[arg(long)]
+        rolling: bool,
+        /// Number of instances to restart at once with --rolling
+        #[arg(short = 'b', long, default_value_t = 1, requires = "rolling")]
+        batch: usize,
+        /// Seconds to wait for a batch's replacements before giving up
+        #[arg(long, default_value_t = 120)]
+        timeout: u64,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
     /// Stop a running instance, or all instances if no instance ID is given
     Stop {
         /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
@@ -249,13 +387,48 @@ enum TaskCommands {
     Invoke {
         /// Content item ID (ULID)
         id: String,
+        /// Set a task parameter. Values are read as JSON where possible
+        /// (`n=10`, `flag=true`, `ids=[1,2]`) and as strings otherwise;
+        /// quote them (`year='"2024"'`) to force a string. Numbers that JSON
+        /// would rewrite (`1.10`, `01234`) are kept as strings. Repeatable.
+        #[arg(long = "param", value_name = "KEY=VALUE")]
+        param: Vec<String>,
+        /// Task parameters as a JSON object
+        #[arg(long, value_name = "JSON")]
+        params_json: Option<String>,
+        /// Read task parameters from a .json, .yaml or .toml file.
+        /// --params-json and --param override keys set here
+        #[arg(long, value_name = "FILE")]
+        params_file: Option<std::path::PathBuf>,
+        /// Wait for the invocation to end and exit with its status: 0 on
+        /// success, the task's exit code (or 1) on failure, 130 if stopped
+        #[arg(long)]
+        wait: bool,
+        /// Stream the task's output until the invocation ends (implies --wait)
+        #[arg(long)]
+        follow: bool,
+        /// Stop waiting after this long (e.g. 30m, 2h) and exit non-zero.
+        /// The invocation keeps running (implies --wait)
+        #[arg(long, value_name = "DURATION", value_parser = ricochet_cli::utils::parse_duration)]
+        timeout: Option<std::time::Duration>,
     },
-    /// Set or update the schedule for a task
+    /// Set or update the schedule for a task, or show it when no cron
+    /// expression is given
+    #[command(args_conflicts_with_subcommands = true)]
     Schedule {
-        /// Content item ID (ULID)
-        id: String,
+        #[command(subcommand)]
+        command: Option<ScheduleCommands>,
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml.
+        /// A lone argument that parses as a cron expression is taken as SCHEDULE
+        id: Option<String>,
         /// Cron expression (e.g. "0 9 * * 1-5" for weekdays at 9am)
-        schedule: String,
+        schedule: Option<String>,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+        /// Also show run times in this IANA time zone (e.g. Europe/Berlin)
+        #[arg(long)]
+        tz: Option<String>,
     },
     /// Show the diff between the local _ricochet.toml and the deployed item.
     /// Use the `update` subcommand to apply it.
@@ -271,6 +444,11 @@ enum TaskCommands {
         #[command(subcommand)]
         command: DeploymentCommands,
     },
+    /// Inspect and stop runs of a task
+    Invocation {
+        #[command(subcommand)]
+        command: InvocationCommands,
+    },
     /// Manage environment variables for a task
     EnvVars {
         #[command(subcommand)]
@@ -288,6 +466,20 @@ enum EnvVarsCommands {
         #[arg(short = 'p', long)]
         path: Option<std::path::PathBuf>,
     },
+    /// Compare the names set on the server with local dotfiles and _ricochet.toml
+    Diff {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Dotenv file to compare against. Repeatable. Defaults to .env and .Renviron
+        #[arg(long, value_name = "PATH")]
+        file: Vec<std::path::PathBuf>,
+        /// Exit with status 1 if the server is missing a local or declared name
+        #[arg(long)]
+        exit_code: bool,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
     /// Delete an environment variable by name
     Delete {
         /// Environment variable name
@@ -306,14 +498,30 @@ enum EnvVarsCommands {
     Set {
         /// Variables to set. `KEY=VALUE` sets it directly. `KEY` alone resolves
         /// the value from .env, .Renviron, or the calling environment.
-        #[arg(value_name = "KEY[=VALUE]", required = true)]
+        #[arg(value_name = "KEY[=VALUE]", required_unless_present = "from_file")]
         env: Vec<String>,
+        /// Set every variable in this dotenv file. Repeatable; later files win
+        #[arg(long, value_name = "PATH")]
+        from_file: Vec<std::path::PathBuf>,
+        /// Only take names from --from-file matching this glob. Repeatable
+        #[arg(long, value_name = "GLOB", requires = "from_file")]
+        only: Vec<String>,
+        /// Skip names from --from-file matching this glob. Repeatable
+        #[arg(long, value_name = "GLOB", requires = "from_file")]
+        except: Vec<String>,
+        /// Encrypt environment variables even if the server isn't configured,
+        /// so its key can't be checked against a pinned one
+        #[arg(long)]
+        trust_unpinned_key: bool,
         /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
         #[arg(short = 'i', long)]
         id: Option<String>,
         /// Path to _ricochet.toml file
         #[arg(short = 'p', long)]
         path: Option<std::path::PathBuf>,
+        /// Skip confirmation
+        #[arg(short = 'f', long)]
+        force: bool,
     },
     /// Replace all environment variables (any not listed are deleted)
     Replace {
@@ -322,6 +530,19 @@ enum EnvVarsCommands {
         /// entirely to delete all environment variables.
         #[arg(value_name = "KEY[=VALUE]")]
         env: Vec<String>,
+        /// Set every variable in this dotenv file. Repeatable; later files win
+        #[arg(long, value_name = "PATH")]
+        from_file: Vec<std::path::PathBuf>,
+        /// Only take names from --from-file matching this glob. Repeatable
+        #[arg(long, value_name = "GLOB", requires = "from_file")]
+        only: Vec<String>,
+        /// Skip names from --from-file matching this glob. Repeatable
+        #[arg(long, value_name = "GLOB", requires = "from_file")]
+        except: Vec<String>,
+        /// Encrypt environment variables even if the server isn't configured,
+        /// so its key can't be checked against a pinned one
+        #[arg(long)]
+        trust_unpinned_key: bool,
         /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
         #[arg(short = 'i', long)]
         id: Option<String>,
@@ -350,6 +571,121 @@ enum DeploymentCommands {
         /// Deployment ID (ULID)
         id: String,
     },
+    /// Show the build log of a deployment
+    Logs {
+        /// Deployment ID (ULID)
+        id: String,
+        /// Keep streaming new output until the deployment finishes, exiting
+        /// non-zero if it failed
+        #[arg(short = 'f', long)]
+        follow: bool,
+    },
+    /// Roll a content item back to an earlier successful deployment
+    Rollback {
+        /// Content item ID (ULID)
+        id: String,
+        /// Deployment to roll back to (defaults to the last success before the current one)
+        #[arg(long, value_name = "DEPLOYMENT_ID")]
+        to: Option<String>,
+        /// Skip confirmation
+        #[arg(short = 'f', long)]
+        force: bool,
+        /// Wait for the rollback to go live and exit non-zero unless it succeeds
+        #[arg(long)]
+        wait: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommands {
+    /// Show the schedule and when it next fires
+    Show {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+        /// Number of upcoming runs to list
+        #[arg(short = 'n', long, default_value_t = 5)]
+        count: usize,
+        /// Also show run times in this IANA time zone (e.g. Europe/Berlin)
+        #[arg(long)]
+        tz: Option<String>,
+    },
+    /// List when a cron expression fires, without contacting a server
+    Preview {
+        /// Cron expression (e.g. "0 9 * * 1-5" for weekdays at 9am)
+        schedule: String,
+        /// Number of upcoming runs to list
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+        /// Also show run times in this IANA time zone (e.g. Europe/Berlin).
+        /// The expression is read in UTC, as the server does; runs whose
+        /// local time moves with DST are flagged
+        #[arg(long)]
+        tz: Option<String>,
+    },
+    /// Remove the schedule
+    Clear {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Stop the schedule from firing, keeping its cron expression
+    Pause {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Let a paused schedule fire again
+    Resume {
+        /// Content item ID (ULID). If not provided, will read from local _ricochet.toml
+        id: Option<String>,
+        /// Path to _ricochet.toml file
+        #[arg(short = 'p', long)]
+        path: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum InvocationCommands {
+    /// List invocations of a task
+    List {
+        /// Content item ID (ULID)
+        id: String,
+        /// Fields to display: 'all' or comma-separated names
+        /// (id, content_id, status, started_at, ended_at, duration, trigger, exit_code)
+        #[arg(long, value_delimiter = ',')]
+        fields: Option<Vec<String>>,
+    },
+    /// Get a specific invocation
+    Get {
+        /// Content item ID (ULID)
+        id: String,
+        /// Invocation ID (ULID)
+        invocation_id: String,
+    },
+    /// Show the output of an invocation
+    Logs {
+        /// Content item ID (ULID)
+        id: String,
+        /// Invocation ID (ULID)
+        invocation_id: String,
+        /// Keep streaming new output until the invocation ends
+        #[arg(short = 'f', long)]
+        follow: bool,
+    },
+    /// Stop a running invocation
+    Stop {
+        /// Content item ID (ULID)
+        id: String,
+        /// Invocation ID (ULID)
+        invocation_id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -392,6 +728,14 @@ enum ServerCommands {
         /// Server name to set as default
         name: String,
     },
+    /// Trust the server's current env var encryption key, replacing the pinned one
+    TrustKey {
+        /// Server name whose key to trust
+        name: String,
+        /// Skip confirmation prompt
+        #[arg(short = 'f', long)]
+        force: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -461,13 +805,31 @@ async fn main() -> Result<()> {
             name,
             description,
             env,
+            from_file,
+            only,
+            except,
+            yes,
+            trust_unpinned_key,
             git,
             branch,
             repo_path,
             config_path,
             credential,
+            wait,
+            logs,
+            dry_run,
+            bundle,
+            gitignore,
+            force,
+            diff,
+            check_path,
+            expect_status,
+            timeout,
+            rollback_on_failure,
         }) => {
-            if let Some(git) = git {
+            if dry_run {
+                commands::deploy::dry_run(&path, gitignore, cli.format)?;
+            } else if let Some(git) = git {
                 commands::deploy::deploy_git(
                     &config,
                     cli.server.as_deref(),
@@ -479,18 +841,53 @@ async fn main() -> Result<()> {
                 )
                 .await?;
             } else {
-                commands::deploy::deploy(
+                let result = commands::deploy::deploy(
                     &config,
                     cli.server.as_deref(),
                     path,
-                    name,
-                    description,
-                    env,
-                    cli.debug,
+                    commands::deploy::DeployOptions {
+                        name,
+                        description,
+                        env,
+                        env_files: env_vars::EnvFiles {
+                            files: from_file,
+                            only,
+                            except,
+                        },
+                        yes,
+                        trust_unpinned_key,
+                        wait,
+                        logs,
+                        bundle,
+                        gitignore,
+                        force,
+                        diff,
+                        smoke: check_path.map(|path| app::smoke::SmokeCheck {
+                            path,
+                            expect_status,
+                            timeout,
+                        }),
+                        rollback_on_failure,
+                        debug: cli.debug,
+                    },
                 )
-                .await?;
+                .await;
+                // A deployment that fails its smoke check gets its own exit status
+                if let Err(e) = &result
+                    && e.downcast_ref::<app::smoke::SmokeCheckFailed>().is_some()
+                {
+                    std::process::exit(app::smoke::SMOKE_FAILED_EXIT_CODE);
+                }
+                result?;
             }
         }
+        Some(Commands::Bundle {
+            path,
+            output,
+            gitignore,
+        }) => {
+            commands::bundle::bundle(&path, &output, gitignore, cli.debug).await?;
+        }
         Some(Commands::Delete { id, force }) => {
             commands::delete::delete(&config, cli.server.as_deref(), &id, force).await?;
         }
@@ -499,7 +896,17 @@ async fn main() -> Result<()> {
                 "{} `ricochet invoke` is deprecated. Use `ricochet task invoke` instead.",
                 "warning:".yellow().bold()
             );
-            item::invoke::invoke(&config, cli.server.as_deref(), &id, cli.format).await?;
+            item::invoke::invoke(
+                &config,
+                cli.server.as_deref(),
+                &id,
+                None,
+                false,
+                false,
+                None,
+                cli.format,
+            )
+            .await?;
         }
         Some(Commands::Config { show_all }) => {
             commands::config::show(&config, show_all)?;
@@ -543,6 +950,80 @@ async fn main() -> Result<()> {
                 )
                 .await?;
             }
+            ItemCommands::Logs {
+                id,
+                instance,
+                follow,
+                since,
+                grep,
+                path,
+            } => {
+                app::logs::logs(
+                    &config,
+                    cli.server.as_deref(),
+                    id.as_deref(),
+                    path.as_deref(),
+                    instance.as_deref(),
+                    follow,
+                    since.as_deref(),
+                    grep.as_deref(),
+                    cli.format,
+                )
+                .await?;
+            }
+            ItemCommands::Scale {
+                id,
+                min_instances,
+                max_instances,
+                spawn_threshold,
+                force,
+                write_toml,
+                path,
+            } => {
+                app::scale::scale(
+                    &config,
+                    cli.server.as_deref(),
+                    id.as_deref(),
+                    path.as_deref(),
+                    app::scale::Scale {
+                        min_instances,
+                        max_instances,
+                        spawn_threshold,
+                    },
+                    force,
+                    write_toml,
+                    cli.format,
+                )
+                .await?;
+            }
+            ItemCommands::Proxy { id, port, path } => {
+                app::proxy::proxy(
+                    &config,
+                    cli.server.as_deref(),
+                    id.as_deref(),
+                    path.as_deref(),
+                    port,
+                )
+                .await?;
+            }
+            ItemCommands::Restart {
+                id,
+                rolling,
+                batch,
+                timeout,
+                path,
+            } => {
+                app::instances::restart_instances(
+                    &config,
+                    cli.server.as_deref(),
+                    id.as_deref(),
+                    path.as_deref(),
+                    rolling,
+                    batch,
+                    std::time::Duration::from_secs(timeout),
+                )
+                .await?;
+            }
             ItemCommands::Stop { id, pid, path } => {
                 app::instances::stop_instance(
                     &config,
@@ -598,6 +1079,26 @@ async fn main() -> Result<()> {
                     )
                     .await?;
                 }
+                DeploymentCommands::Logs { id, follow } => {
+                    item::deployment::deployment_logs(&config, cli.server.as_deref(), &id, follow)
+                        .await?;
+                }
+                DeploymentCommands::Rollback {
+                    id,
+                    to,
+                    force,
+                    wait,
+                } => {
+                    item::deployment::rollback(
+                        &config,
+                        cli.server.as_deref(),
+                        &id,
+                        to.as_deref(),
+                        force,
+                        wait,
+                    )
+                    .await?;
+                }
             },
             ItemCommands::EnvVars { command } => match command {
                 EnvVarsCommands::Get { id, path } => {
@@ -610,6 +1111,32 @@ async fn main() -> Result<()> {
                     )
                     .await?;
                 }
+                EnvVarsCommands::Diff {
+                    id,
+                    file,
+                    exit_code,
+                    path,
+                } => {
+                    let result = item::env_vars::diff_env_vars(
+                        &config,
+                        cli.server.as_deref(),
+                        id.as_deref(),
+                        path.as_deref(),
+                        &file,
+                        exit_code,
+                        cli.format,
+                    )
+                    .await;
+                    // Drift is a finding rather than a failure to run, so it gets
+                    // a plain message instead of an error
+                    if let Err(e) = &result
+                        && let Some(drift) = e.downcast_ref::<item::env_vars::EnvVarsDrift>()
+                    {
+                        eprintln!("{} {}", "✗".red().bold(), drift);
+                        std::process::exit(1);
+                    }
+                    result?;
+                }
                 EnvVarsCommands::Delete {
                     name,
                     id,
@@ -627,13 +1154,29 @@ async fn main() -> Result<()> {
                     )
                     .await?;
                 }
-                EnvVarsCommands::Set { id, path, env } => {
+                EnvVarsCommands::Set {
+                    id,
+                    path,
+                    env,
+                    from_file,
+                    only,
+                    except,
+                    trust_unpinned_key,
+                    force,
+                } => {
                     item::env_vars::set_env_vars(
                         &config,
                         cli.server.as_deref(),
                         id.as_deref(),
                         path.as_deref(),
                         &env,
+                        &env_vars::EnvFiles {
+                            files: from_file,
+                            only,
+                            except,
+                        },
+                        force,
+                        trust_unpinned_key,
                         cli.format,
                     )
                     .await?;
@@ -642,6 +1185,10 @@ async fn main() -> Result<()> {
                     id,
                     path,
                     env,
+                    from_file,
+                    only,
+                    except,
+                    trust_unpinned_key,
                     force,
                 } => {
                     item::env_vars::replace_env_vars(
@@ -650,7 +1197,13 @@ async fn main() -> Result<()> {
                         id.as_deref(),
                         path.as_deref(),
                         &env,
+                        &env_vars::EnvFiles {
+                            files: from_file,
+                            only,
+                            except,
+                        },
                         force,
+                        trust_unpinned_key,
                         cli.format,
                     )
                     .await?;
@@ -678,19 +1231,131 @@ async fn main() -> Result<()> {
             TaskCommands::Toml { id, path } => {
                 item::toml::get_toml(&config, id, path).await?;
             }
-            TaskCommands::Invoke { id } => {
-                item::invoke::invoke(&config, cli.server.as_deref(), &id, cli.format).await?;
-            }
-            TaskCommands::Schedule { id, schedule } => {
-                item::schedule::schedule_task(
+            TaskCommands::Invoke {
+                id,
+                param,
+                params_json,
+                params_file,
+                wait,
+                follow,
+                timeout,
+            } => {
+                let params = item::invoke::resolve_params(
+                    &param,
+                    params_json.as_deref(),
+                    params_file.as_deref(),
+                )?;
+                let result = item::invoke::invoke(
                     &config,
                     cli.server.as_deref(),
                     &id,
-                    &schedule,
+                    params,
+                    wait,
+                    follow,
+                    timeout,
                     cli.format,
                 )
-                .await?;
+                .await;
+                // Pass the task's outcome on as our exit status so scripts can chain tasks
+                if let Err(e) = &result
+                    && let Some(failed) = e.downcast_ref::<item::invoke::InvocationFailed>()
+                {
+                    std::process::exit(failed.code);
+                }
+                result?;
             }
+            TaskCommands::Schedule {
+                command,
+                id,
+                schedule,
+                path,
+                tz,
+            } => match command {
+                None => {
+                    let (id, schedule) = item::schedule::split_schedule_args(id, schedule);
+                    match schedule {
+                        Some(schedule) => {
+                            let id = item::resolve_id(id.as_deref(), path.as_deref())?;
+                            item::schedule::schedule_task(
+                                &config,
+                                cli.server.as_deref(),
+                                &id,
+                                &schedule,
+                                tz.as_deref(),
+                                cli.format,
+                            )
+                            .await?;
+                        }
+                        None => {
+                            item::schedule::show_schedule(
+                                &config,
+                                cli.server.as_deref(),
+                                id.as_deref(),
+                                path.as_deref(),
+                                5,
+                                tz.as_deref(),
+                                cli.format,
+                            )
+                            .await?;
+                        }
+                    }
+                }
+                Some(ScheduleCommands::Show {
+                    id,
+                    path,
+                    count,
+                    tz,
+                }) => {
+                    item::schedule::show_schedule(
+                        &config,
+                        cli.server.as_deref(),
+                        id.as_deref(),
+                        path.as_deref(),
+                        count,
+                        tz.as_deref(),
+                        cli.format,
+                    )
+                    .await?;
+                }
+                Some(ScheduleCommands::Preview {
+                    schedule,
+                    count,
+                    tz,
+                }) => {
+                    item::schedule::preview_schedule(&schedule, count, tz.as_deref(), cli.format)?;
+                }
+                Some(ScheduleCommands::Clear { id, path }) => {
+                    item::schedule::clear_schedule(
+                        &config,
+                        cli.server.as_deref(),
+                        id.as_deref(),
+                        path.as_deref(),
+                    )
+                    .await?;
+                }
+                Some(ScheduleCommands::Pause { id, path }) => {
+                    item::schedule::set_schedule_paused(
+                        &config,
+                        cli.server.as_deref(),
+                        id.as_deref(),
+                        path.as_deref(),
+                        true,
+                        cli.format,
+                    )
+                    .await?;
+                }
+                Some(ScheduleCommands::Resume { id, path }) => {
+                    item::schedule::set_schedule_paused(
+                        &config,
+                        cli.server.as_deref(),
+                        id.as_deref(),
+                        path.as_deref(),
+                        false,
+                        cli.format,
+                    )
+                    .await?;
+                }
+            },
             TaskCommands::Settings { command, path } => match command {
                 None => {
                     item::settings::preview(
@@ -736,6 +1401,71 @@ async fn main() -> Result<()> {
                     )
                     .await?;
                 }
+                DeploymentCommands::Logs { id, follow } => {
+                    item::deployment::deployment_logs(&config, cli.server.as_deref(), &id, follow)
+                        .await?;
+                }
+                DeploymentCommands::Rollback {
+                    id,
+                    to,
+                    force,
+                    wait,
+                } => {
+                    item::deployment::rollback(
+                        &config,
+                        cli.server.as_deref(),
+                        &id,
+                        to.as_deref(),
+                        force,
+                        wait,
+                    )
+                    .await?;
+                }
+            },
+            TaskCommands::Invocation { command } => match command {
+                InvocationCommands::List { id, fields } => {
+                    item::invocation::list_invocations(
+                        &config,
+                        cli.server.as_deref(),
+                        &id,
+                        fields,
+                        cli.format,
+                    )
+                    .await?;
+                }
+                InvocationCommands::Get { id, invocation_id } => {
+                    item::invocation::get_invocation(
+                        &config,
+                        cli.server.as_deref(),
+                        &id,
+                        &invocation_id,
+                        cli.format,
+                    )
+                    .await?;
+                }
+                InvocationCommands::Logs {
+                    id,
+                    invocation_id,
+                    follow,
+                } => {
+                    item::invocation::invocation_logs(
+                        &config,
+                        cli.server.as_deref(),
+                        &id,
+                        &invocation_id,
+                        follow,
+                    )
+                    .await?;
+                }
+                InvocationCommands::Stop { id, invocation_id } => {
+                    item::invocation::stop_invocation(
+                        &config,
+                        cli.server.as_deref(),
+                        &id,
+                        &invocation_id,
+                    )
+                    .await?;
+                }
             },
             TaskCommands::EnvVars { command } => match command {
                 EnvVarsCommands::Get { id, path } => {
@@ -748,6 +1478,32 @@ async fn main() -> Result<()> {
                     )
                     .await?;
                 }
+                EnvVarsCommands::Diff {
+                    id,
+                    file,
+                    exit_code,
+                    path,
+                } => {
+                    let result = item::env_vars::diff_env_vars(
+                        &config,
+                        cli.server.as_deref(),
+                        id.as_deref(),
+                        path.as_deref(),
+                        &file,
+                        exit_code,
+                        cli.format,
+                    )
+                    .await;
+                    // Drift is a finding rather than a failure to run, so it gets
+                    // a plain message instead of an error
+                    if let Err(e) = &result
+                        && let Some(drift) = e.downcast_ref::<item::env_vars::EnvVarsDrift>()
+                    {
+                        eprintln!("{} {}", "✗".red().bold(), drift);
+                        std::process::exit(1);
+                    }
+                    result?;
+                }
                 EnvVarsCommands::Delete {
                     name,
                     id,
@@ -765,13 +1521,29 @@ async fn main() -> Result<()> {
                     )
                     .await?;
                 }
-                EnvVarsCommands::Set { id, path, env } => {
+                EnvVarsCommands::Set {
+                    id,
+                    path,
+                    env,
+                    from_file,
+                    only,
+                    except,
+                    trust_unpinned_key,
+                    force,
+                } => {
                     item::env_vars::set_env_vars(
                         &config,
                         cli.server.as_deref(),
                         id.as_deref(),
                         path.as_deref(),
                         &env,
+                        &env_vars::EnvFiles {
+                            files: from_file,
+                            only,
+                            except,
+                        },
+                        force,
+                        trust_unpinned_key,
                         cli.format,
                     )
                     .await?;
@@ -780,6 +1552,10 @@ async fn main() -> Result<()> {
                     id,
                     path,
                     env,
+                    from_file,
+                    only,
+                    except,
+                    trust_unpinned_key,
                     force,
                 } => {
                     item::env_vars::replace_env_vars(
@@ -788,7 +1564,13 @@ async fn main() -> Result<()> {
                         id.as_deref(),
                         path.as_deref(),
                         &env,
+                        &env_vars::EnvFiles {
+                            files: from_file,
+                            only,
+                            except,
+                        },
                         force,
+                        trust_unpinned_key,
                         cli.format,
                     )
                     .await?;
@@ -808,6 +1590,9 @@ async fn main() -> Result<()> {
             ServerCommands::SetDefault { name } => {
                 commands::server::set_default(&mut config, name)?;
             }
+            ServerCommands::TrustKey { name, force } => {
+                commands::server::trust_key(&mut config, name, force).await?;
+            }
         },
         Some(Commands::User { command }) => match command {
             UserCommands::Credentials { user_id, r#type } => {
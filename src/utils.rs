@@ -1,8 +1,19 @@
 use anyhow::{Context, Result};
 use globset::{Glob, GlobSetBuilder};
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use indicatif::ProgressBar;
+use rsa::sha2::{Digest, Sha256};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::io::IsTerminal;
+use std::io::{IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context as TaskContext, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::{mpsc, oneshot};
 
 /// Check if we're running in a non-interactive environment (tests, CI, etc.)
 ///
@@ -20,153 +31,750 @@ pub fn is_non_interactive() -> bool {
         || std::env::var("RICOCHET_NON_INTERACTIVE").is_ok()
 }
 
-/// Prepare a list of files to bundle based on include/exclude patterns
+/// Paths that are never bundled: virtual environments, renv caches, and
+/// Python bytecode caches. `__pycache__` can appear at any nesting level, so
+/// it is matched recursively.
+const BUNDLE_BLACKLIST: &[&str] = &[
+    ".venv",
+    ".venv/**",
+    ".renv",
+    ".renv/**",
+    "__pycache__",
+    "__pycache__/**",
+    "**/__pycache__",
+    "**/__pycache__/**",
+];
+
+/// Path inside a bundle of the file listing the SHA-256 of every other file.
+/// `.ricochet` is never bundled from the content directory, so this can't
+/// clash with a project's own files.
+pub const MANIFEST_FILE: &str = ".ricochet/MANIFEST";
+
+/// Directory holding ricochet's local state: deployment records and the
+/// bundle manifest. Nothing under it is bundled from the content directory.
+pub const LOCAL_STATE_DIR: &str = ".ricochet";
+
+/// Ignore file read from any directory of a bundle, using gitignore syntax.
+pub const IGNORE_FILE: &str = ".ricochetignore";
+
+/// The rule that kept a path out of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Matched one of the always-excluded patterns
+    Builtin { pattern: String },
+    /// Lives under [`LOCAL_STATE_DIR`], which ricochet keeps to itself
+    LocalState,
+    /// Matched a pattern in a `.ricochetignore` or `.gitignore` file
+    Ignored { file: PathBuf, pattern: String },
+    /// `include` patterns are set and none of them matched
+    NotIncluded,
+    /// Matched an `exclude` pattern
+    Excluded { pattern: String },
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Builtin { pattern } => write!(f, "always excluded ({pattern})"),
+            SkipReason::LocalState => {
+                write!(
+                    f,
+                    "{LOCAL_STATE_DIR}/ is reserved for ricochet and never bundled"
+                )
+            }
+            SkipReason::Ignored { file, pattern } => {
+                write!(f, "ignored by {pattern} in {}", file.display())
+            }
+            SkipReason::NotIncluded => write!(f, "not matched by include"),
+            SkipReason::Excluded { pattern } => write!(f, "excluded by {pattern}"),
+        }
+    }
+}
+
+/// Which paths under a directory go into a bundle, and which were left out.
+#[derive(Debug, Default)]
+pub struct BundlePlan {
+    /// Paths to bundle, directories included, in walk order
+    pub files: Vec<PathBuf>,
+    /// Files left out and the rule responsible. Always-excluded and ignored
+    /// directories are listed once instead of file by file.
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+pub(crate) fn build_globset(patterns: &[String]) -> Result<globset::GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Load the ignore files of a single directory, `.ricochetignore` first so
+/// it can re-include what `.gitignore` leaves out.
+fn load_ignore_files(dir: &Path, use_gitignore: bool) -> Result<Vec<Gitignore>> {
+    let mut names = vec![IGNORE_FILE];
+    if use_gitignore {
+        names.push(".gitignore");
+    }
+
+    let mut matchers = Vec::new();
+    for name in names {
+        let path = dir.join(name);
+        if !path.is_file() {
+            continue;
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(err) = builder.add(&path) {
+            return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+        }
+        matchers.push(builder.build()?);
+    }
+    Ok(matchers)
+}
+
+/// Check `path` against the ignore files of its ancestors up to `root`. As in
+/// git, the closest ignore file with a matching rule decides.
+fn ignored_by(
+    ignores: &HashMap<PathBuf, Vec<Gitignore>>,
+    root: &Path,
+    path: &Path,
+    is_dir: bool,
+) -> Option<SkipReason> {
+    for ancestor in path.ancestors().skip(1).take_while(|a| a.starts_with(root)) {
+        for matcher in ignores.get(ancestor).into_iter().flatten() {
+            match matcher.matched(path, is_dir) {
+                Match::None => continue,
+                Match::Whitelist(_) => return None,
+                Match::Ignore(glob) => {
+                    let file = glob.from().unwrap_or(matcher.path());
+                    return Some(SkipReason::Ignored {
+                        file: file.strip_prefix(root).unwrap_or(file).to_path_buf(),
+                        pattern: glob.original().to_string(),
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Prepare a list of files to bundle based on ignore files and include/exclude patterns
 ///
 /// Logic:
 /// 1. Always exclude .venv and .renv directories
-/// 2. If include patterns are specified, ONLY include paths matching those patterns
-/// 3. Then exclude any paths matching the exclude patterns
-/// 4. Otherwise include everything (except blacklisted directories)
+/// 2. Skip paths ignored by a `.ricochetignore` (and, if `use_gitignore` is
+///    set, `.gitignore`) in the same or a parent directory
+/// 3. If include patterns are specified, ONLY include paths matching those patterns
+/// 4. Then exclude any paths matching the exclude patterns
+/// 5. Otherwise include everything (except blacklisted directories)
 pub fn prepare_bundle(
     dir: &Path,
     include: Option<Vec<String>>,
     exclude: Option<Vec<String>>,
 ) -> Result<Vec<PathBuf>> {
-    // prevent including virtual environments, renv caches, and Python bytecode caches
-    let mut blacklist_builder = GlobSetBuilder::new();
-    blacklist_builder.add(Glob::new(".venv")?);
-    blacklist_builder.add(Glob::new(".venv/**")?);
-    blacklist_builder.add(Glob::new(".renv")?);
-    blacklist_builder.add(Glob::new(".renv/**")?);
-    // __pycache__ can appear at any nesting level, so match it recursively
-    blacklist_builder.add(Glob::new("__pycache__")?);
-    blacklist_builder.add(Glob::new("__pycache__/**")?);
-    blacklist_builder.add(Glob::new("**/__pycache__")?);
-    blacklist_builder.add(Glob::new("**/__pycache__/**")?);
-    let blacklist = blacklist_builder.build()?;
-
-    let include_matcher = if let Some(patterns) = include {
-        let mut builder = GlobSetBuilder::new();
-        for pattern in patterns {
-            builder.add(Glob::new(&pattern)?);
-        }
-        Some(builder.build()?)
-    } else {
-        None
-    };
-
-    let exclude_matcher = if let Some(patterns) = exclude {
-        let mut builder = GlobSetBuilder::new();
-        for pattern in patterns {
-            builder.add(Glob::new(&pattern)?);
-        }
-        Some(builder.build()?)
-    } else {
-        None
-    };
-
-    let mut files_to_bundle = Vec::new();
+    Ok(plan_bundle(dir, include, exclude, false)?.files)
+}
 
-    for entry in walkdir::WalkDir::new(dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
+/// Like [`prepare_bundle`], but also records why each skipped file was left out.
+pub fn plan_bundle(
+    dir: &Path,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    use_gitignore: bool,
+) -> Result<BundlePlan> {
+    let blacklist_patterns: Vec<String> = BUNDLE_BLACKLIST.iter().map(|p| p.to_string()).collect();
+    let blacklist = build_globset(&blacklist_patterns)?;
+    let include_matcher = include.as_deref().map(build_globset).transpose()?;
+    let exclude_patterns = exclude.unwrap_or_default();
+    let exclude_matcher = build_globset(&exclude_patterns)?;
+
+    let mut plan = BundlePlan::default();
+    let mut ignores: HashMap<PathBuf, Vec<Gitignore>> = HashMap::new();
+    let mut walker = walkdir::WalkDir::new(dir).into_iter();
+
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
         let relative_path = entry.path().strip_prefix(dir).unwrap_or(entry.path());
-
-        if blacklist.is_match(relative_path) {
+        let is_dir = entry.file_type().is_dir();
+
+        // Walk into the local state directory so each file dropped from it is
+        // reported, not just the directory
+        if relative_path.starts_with(LOCAL_STATE_DIR) {
+            if !is_dir {
+                plan.skipped
+                    .push((relative_path.to_path_buf(), SkipReason::LocalState));
+            }
             continue;
         }
 
-        // include / exclude files based on include
-        if let Some(ref matcher) = include_matcher
-            && !matcher.is_match(relative_path)
-        {
+        if let Some(&i) = blacklist.matches(relative_path).first() {
+            // Everything below a blacklisted directory is blacklisted too
+            if is_dir {
+                walker.skip_current_dir();
+            }
+            plan.skipped.push((
+                relative_path.to_path_buf(),
+                SkipReason::Builtin {
+                    pattern: blacklist_patterns[i].clone(),
+                },
+            ));
             continue;
         }
 
-        if let Some(ref matcher) = exclude_matcher
-            && matcher.is_match(relative_path)
-        {
+        // Git never looks inside an ignored directory, so neither do we
+        if let Some(reason) = ignored_by(&ignores, dir, entry.path(), is_dir) {
+            if is_dir {
+                walker.skip_current_dir();
+            }
+            plan.skipped.push((relative_path.to_path_buf(), reason));
             continue;
         }
 
-        files_to_bundle.push(entry.path().to_path_buf());
+        if is_dir {
+            let matchers = load_ignore_files(entry.path(), use_gitignore)?;
+            if !matchers.is_empty() {
+                ignores.insert(entry.path().to_path_buf(), matchers);
+            }
+        }
+
+        // Directories are never added to the tarball themselves, so only
+        // report skipped files
+        let reason = if let Some(ref matcher) = include_matcher
+            && !matcher.is_match(relative_path)
+        {
+            Some(SkipReason::NotIncluded)
+        } else {
+            exclude_matcher
+                .matches(relative_path)
+                .first()
+                .map(|&i| SkipReason::Excluded {
+                    pattern: exclude_patterns[i].clone(),
+                })
+        };
+
+        match reason {
+            Some(reason) => {
+                if !is_dir {
+                    plan.skipped.push((relative_path.to_path_buf(), reason));
+                }
+            }
+            None => plan.files.push(entry.path().to_path_buf()),
+        }
     }
 
-    Ok(files_to_bundle)
+    Ok(plan)
 }
 
+/// Build the bundle for `dir` and write it to `output`. Setting `cancel`
+/// stops the build part way, leaving `output` as it was.
+#[allow(clippy::too_many_arguments)]
 pub fn create_bundle(
     dir: &Path,
     output: &Path,
     include: Option<Vec<String>>,
     exclude: Option<Vec<String>>,
     extra_root_files: &[(PathBuf, String)],
+    use_gitignore: bool,
     debug: bool,
-) -> Result<()> {
-    let tar_gz = File::create(output)?;
-    let enc = flate2::write::GzEncoder::new(tar_gz, flate2::Compression::default());
-    let mut tar = tar::Builder::new(enc);
-
-    let files_to_bundle = prepare_bundle(dir, include, exclude)?;
+    cancel: Option<&AtomicBool>,
+) -> Result<String> {
+    let mut plan = plan_bundle(dir, include, exclude, use_gitignore)?;
+
+    // Don't pack a previous bundle written into the content directory
+    if let Ok(previous) = output.canonicalize()
+        && let Ok(root) = dir.canonicalize()
+        && let Ok(name) = previous.strip_prefix(&root)
+    {
+        plan.files
+            .retain(|path| path.strip_prefix(dir).unwrap_or(path) != name);
+    }
 
     if debug {
-        println!("\nDebug: Files being bundled:");
-        for path in &files_to_bundle {
-            if path.is_file()
-                && let Ok(metadata) = std::fs::metadata(path)
-            {
-                let size = metadata.len();
-                let relative_path = path.strip_prefix(dir).unwrap_or(path);
-                println!("  {} - {}", relative_path.display(), format_size(size));
+        print_bundle_debug(dir, &plan, extra_root_files);
+    }
+
+    // Write next to the destination and rename, so a failed or cancelled run
+    // never leaves a truncated bundle behind. The temp file is removed when
+    // dropped, which happens on every error path, including cancellation.
+    let output_dir = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::NamedTempFile::new_in(output_dir)?;
+    let (_, digest) = write_bundle(
+        tmp.as_file_mut(),
+        dir,
+        &plan.files,
+        extra_root_files,
+        None,
+        cancel,
+    )?;
+    tmp.persist(output)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    Ok(digest.sha256)
+}
+
+/// List the files a bundle will contain and the ones it skips, for `--debug`.
+pub(crate) fn print_bundle_debug(
+    dir: &Path,
+    plan: &BundlePlan,
+    extra_root_files: &[(PathBuf, String)],
+) {
+    println!("\nDebug: Files being bundled:");
+    for path in &plan.files {
+        if path.is_file()
+            && let Ok(metadata) = std::fs::metadata(path)
+        {
+            let size = metadata.len();
+            let relative_path = path.strip_prefix(dir).unwrap_or(path);
+            println!("  {} - {}", relative_path.display(), format_size(size));
+        }
+    }
+    for (source, name) in extra_root_files {
+        if let Ok(metadata) = std::fs::metadata(source) {
+            println!(
+                "  {} (from {}) - {}",
+                name,
+                source.display(),
+                format_size(metadata.len())
+            );
+        }
+    }
+    if !plan.skipped.is_empty() {
+        println!("\nDebug: Files skipped:");
+        for (path, reason) in &plan.skipped {
+            println!("  {} - {}", path.display(), reason);
+        }
+    }
+    println!();
+}
+
+/// Total uncompressed size in bytes of the files that go into a bundle.
+pub fn bundle_source_size(
+    files: &[PathBuf],
+    extra_root_files: &[(PathBuf, String)],
+) -> Result<u64> {
+    let mut total = 0;
+    for path in files.iter().chain(extra_root_files.iter().map(|(p, _)| p)) {
+        let metadata = std::fs::metadata(path)?;
+        if metadata.is_file() {
+            total += metadata.len();
+        }
+    }
+    Ok(total)
+}
+
+/// How much of the gzipped tarball the bundling thread hands over at a time.
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Build a bundle on a blocking thread and read the gzipped tarball as it is
+/// produced, so it can be uploaded without first being written to disk.
+/// `progress` advances by the number of source bytes packed. Dropping the
+/// reader stops the producer. The returned receiver yields the bundle's
+/// digest and manifest once it has been read to the end.
+pub fn stream_bundle(
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+    extra_root_files: Vec<(PathBuf, String)>,
+    progress: Option<ProgressBar>,
+) -> (BundleReader, oneshot::Receiver<BundleDigest>) {
+    let (tx, rx) = mpsc::channel(8);
+    let (digest_tx, digest_rx) = oneshot::channel();
+
+    tokio::task::spawn_blocking(move || {
+        let writer = ChannelWriter {
+            tx: tx.clone(),
+            buf: Vec::with_capacity(STREAM_CHUNK_SIZE),
+        };
+        let result = write_bundle(
+            writer,
+            &dir,
+            &files,
+            &extra_root_files,
+            progress.as_ref(),
+            None,
+        )
+        .and_then(|(mut writer, digest)| {
+            writer.flush()?;
+            Ok(digest)
+        });
+        // Sends fail only if the reader is gone, in which case nobody is listening
+        match result {
+            Ok(digest) => {
+                let _ = digest_tx.send(digest);
+            }
+            Err(e) => {
+                let _ = tx.blocking_send(Err(std::io::Error::other(format!("{e:#}"))));
             }
         }
-        for (source, name) in extra_root_files {
-            if let Ok(metadata) = std::fs::metadata(source) {
-                println!(
-                    "  {} (from {}) - {}",
-                    name,
-                    source.display(),
-                    format_size(metadata.len())
-                );
+    });
+
+    let reader = BundleReader {
+        rx,
+        chunk: Vec::new(),
+        pos: 0,
+    };
+    (reader, digest_rx)
+}
+
+/// The reading end of [`stream_bundle`]. Yields an error instead of a
+/// truncated archive if bundling fails part way.
+pub struct BundleReader {
+    rx: mpsc::Receiver<std::io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl AsyncRead for BundleReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        loop {
+            if self.pos < self.chunk.len() {
+                let n = buf.remaining().min(self.chunk.len() - self.pos);
+                let start = self.pos;
+                buf.put_slice(&self.chunk[start..start + n]);
+                self.pos += n;
+                return Poll::Ready(Ok(()));
+            }
+
+            match std::task::ready!(self.rx.poll_recv(cx)) {
+                Some(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                // The producer finished and dropped its sender
+                None => return Poll::Ready(Ok(())),
             }
         }
-        println!();
     }
+}
 
-    // Add files to tar (directories will be created automatically)
-    for path in files_to_bundle {
-        // Only add files, skip directories
-        if !path.is_file() {
-            continue;
+/// The writing end of [`stream_bundle`], used from the blocking thread.
+struct ChannelWriter {
+    tx: mpsc::Sender<std::io::Result<Vec<u8>>>,
+    buf: Vec<u8>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= STREAM_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
         }
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(STREAM_CHUNK_SIZE));
+        self.tx.blocking_send(Ok(chunk)).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "the upload stopped reading the bundle",
+            )
+        })
+    }
+}
 
-        let relative_path = path.strip_prefix(dir).unwrap_or(&path);
+/// Hashes a source file as it is read and advances a progress bar by the
+/// bytes read. Fails the read once `cancel` is set.
+struct SourceReader<'a, R> {
+    inner: R,
+    hasher: Sha256,
+    progress: Option<&'a ProgressBar>,
+    cancel: Option<&'a AtomicBool>,
+}
 
-        // Skip empty paths (root directory)
-        if relative_path.as_os_str().is_empty() {
-            continue;
+impl<R: Read> Read for SourceReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if is_cancelled(self.cancel) {
+            return Err(std::io::Error::other("bundling was cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        if let Some(pb) = self.progress {
+            pb.inc(n as u64);
         }
+        Ok(n)
+    }
+}
+
+fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
+    cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+}
 
-        tar.append_path_with_name(&path, relative_path)
-            .context(format!(
-                "Failed to add {} to bundle",
-                relative_path.display()
-            ))?;
+/// Size in bytes and SHA-256 of the gzipped tarball [`create_bundle`] would
+/// write for `files`, computed without touching the disk.
+pub fn measure_bundle(
+    dir: &Path,
+    files: &[PathBuf],
+    extra_root_files: &[(PathBuf, String)],
+) -> Result<(u64, String)> {
+    let (counter, digest) = write_bundle(ByteCounter(0), dir, files, extra_root_files, None, None)?;
+    Ok((counter.0, digest.sha256))
+}
+
+/// A writer that discards its input and counts the bytes.
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
     }
 
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// What identifies the contents of a bundle.
+#[derive(Debug, Clone)]
+pub struct BundleDigest {
+    /// SHA-256 of the gzipped tarball, hex-encoded
+    pub sha256: String,
+    /// The embedded [`MANIFEST_FILE`], one `<sha256>  <path>` line per file
+    pub manifest: String,
+}
+
+/// Pair each of `files` and `extra_root_files` with its path inside the
+/// bundle, sorted by that path.
+fn bundle_entries<'a>(
+    dir: &Path,
+    files: &'a [PathBuf],
+    extra_root_files: &'a [(PathBuf, String)],
+) -> Vec<(&'a Path, PathBuf)> {
+    // Directories are created implicitly, and the root itself has no name
+    let mut entries: Vec<(&Path, PathBuf)> = files
+        .iter()
+        .filter(|path| path.is_file())
+        .map(|path| {
+            (
+                path.as_path(),
+                path.strip_prefix(dir).unwrap_or(path).to_path_buf(),
+            )
+        })
+        .filter(|(_, name)| !name.as_os_str().is_empty())
+        .collect();
     // Add extra files at the bundle root (e.g. uv.lock from a parent directory)
-    for (source, name) in extra_root_files {
-        tar.append_path_with_name(source, name)
-            .context(format!("Failed to add {} to bundle", source.display()))?;
+    entries.extend(
+        extra_root_files
+            .iter()
+            .map(|(source, name)| (source.as_path(), PathBuf::from(name))),
+    );
+    entries.sort_by(|a, b| a.1.cmp(&b.1));
+    entries
+}
+
+/// The manifest a bundle of `files` and `extra_root_files` would embed, as a
+/// map from path inside the bundle to SHA-256, without building the archive.
+pub fn bundle_manifest(
+    dir: &Path,
+    files: &[PathBuf],
+    extra_root_files: &[(PathBuf, String)],
+) -> Result<BTreeMap<String, String>> {
+    bundle_entries(dir, files, extra_root_files)
+        .into_iter()
+        .map(|(source, name)| Ok((name.display().to_string(), file_digest(source)?)))
+        .collect()
+}
+
+/// Parse a [`MANIFEST_FILE`] into a map from path to SHA-256, skipping
+/// malformed lines.
+pub fn parse_manifest(manifest: &str) -> BTreeMap<String, String> {
+    manifest
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .map(|(digest, path)| (path.to_string(), digest.to_string()))
+        .collect()
+}
+
+/// Files that differ between two manifests, each list sorted by path.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compare the manifest of a new bundle against a previous one.
+pub fn diff_manifests(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+    for (path, digest) in new {
+        match old.get(path) {
+            None => diff.added.push(path.clone()),
+            Some(previous) if previous != digest => diff.modified.push(path.clone()),
+            Some(_) => {}
+        }
     }
+    diff.removed = old
+        .keys()
+        .filter(|path| !new.contains_key(*path))
+        .cloned()
+        .collect();
+    diff
+}
 
-    tar.finish().context("Failed to finalize tar bundle")?;
+/// Write a gzipped tarball of `files` and `extra_root_files` to `writer` and
+/// return the writer along with the archive's digest and manifest.
+///
+/// The archive is reproducible: entries are sorted by name, their metadata is
+/// normalized (mtime 0, uid/gid 0, mode 0644 or 0755), and a
+/// [`MANIFEST_FILE`] listing each file's SHA-256 is appended last.
+fn write_bundle<W: Write>(
+    writer: W,
+    dir: &Path,
+    files: &[PathBuf],
+    extra_root_files: &[(PathBuf, String)],
+    progress: Option<&ProgressBar>,
+    cancel: Option<&AtomicBool>,
+) -> Result<(W, BundleDigest)> {
+    let entries = bundle_entries(dir, files, extra_root_files);
+
+    let hashing = HashingWriter {
+        inner: writer,
+        hasher: Sha256::new(),
+    };
+    let enc = flate2::write::GzEncoder::new(hashing, flate2::Compression::default());
+    let mut tar = tar::Builder::new(enc);
 
-    Ok(())
+    let mut manifest = String::new();
+    for (source, name) in &entries {
+        if is_cancelled(cancel) {
+            anyhow::bail!("Bundling cancelled");
+        }
+        let digest = append_file(&mut tar, source, name, progress, cancel)
+            .with_context(|| format!("Failed to add {} to bundle", name.display()))?;
+        manifest.push_str(&format!("{digest}  {}\n", name.display()));
+    }
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    header.set_uid(0);
+    header.set_gid(0);
+    tar.append_data(&mut header, MANIFEST_FILE, manifest.as_bytes())
+        .context("Failed to add the manifest to bundle")?;
+
+    let enc = tar.into_inner().context("Failed to finalize tar bundle")?;
+    let hashing = enc.finish()?;
+    let digest = BundleDigest {
+        sha256: format!("{:x}", hashing.hasher.finalize()),
+        manifest,
+    };
+    Ok((hashing.inner, digest))
+}
+
+/// Append `source` to `tar` as `name` with normalized metadata, reporting the
+/// bytes read to `progress`. Returns the SHA-256 of the file's contents.
+fn append_file<W: Write>(
+    tar: &mut tar::Builder<W>,
+    source: &Path,
+    name: &Path,
+    progress: Option<&ProgressBar>,
+    cancel: Option<&AtomicBool>,
+) -> std::io::Result<String> {
+    let file = File::open(source)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_metadata_in_mode(&file.metadata()?, tar::HeaderMode::Deterministic);
+    header.set_mtime(0);
+    let mut reader = SourceReader {
+        inner: file,
+        hasher: Sha256::new(),
+        progress,
+        cancel,
+    };
+    tar.append_data(&mut header, name, &mut reader)?;
+    Ok(format!("{:x}", reader.hasher.finalize()))
+}
+
+/// Passes written bytes through while hashing them.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// SHA-256 of a file's contents, hex-encoded.
+pub fn file_digest(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; STREAM_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Read the `_ricochet.toml` stored at the root of a gzipped tarball.
+pub fn read_bundle_config(bundle: &Path) -> Result<String> {
+    match read_bundle_file(bundle, "_ricochet.toml")? {
+        Some(config) => Ok(config),
+        None => anyhow::bail!(
+            "No _ricochet.toml found at the root of {}",
+            bundle.display()
+        ),
+    }
+}
+
+/// Read the text file `name` from the root of a gzipped tarball, if present.
+pub fn read_bundle_file(bundle: &Path, name: &str) -> Result<Option<String>> {
+    let file = File::open(bundle)
+        .with_context(|| format!("Failed to open bundle {}", bundle.display()))?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+
+    for entry in archive
+        .entries()
+        .with_context(|| format!("{} is not a gzipped tarball", bundle.display()))?
+    {
+        let mut entry = entry?;
+        // Archives built by other tools may prefix entries with `./`
+        let path = entry.path()?.into_owned();
+        if path.strip_prefix(".").unwrap_or(&path) == Path::new(name) {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            return Ok(Some(contents));
+        }
+    }
+
+    Ok(None)
 }
 
-fn format_size(bytes: u64) -> String {
+pub(crate) fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -182,6 +790,18 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Parse a duration given in whole seconds (`90`) or with units (`90s`, `2m`,
+/// `1h30m`).
+pub fn parse_duration(s: &str) -> Result<std::time::Duration> {
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(std::time::Duration::from_secs(secs));
+    }
+    let d: jiff::SignedDuration = s
+        .parse()
+        .with_context(|| format!("Invalid duration '{s}', expected e.g. 90s, 2m or 1h30m"))?;
+    std::time::Duration::try_from(d).with_context(|| format!("Duration '{s}' cannot be negative"))
+}
+
 pub fn format_timestamp(timestamp: &str) -> String {
     if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
         dt.format("%Y-%m-%d %H:%M:%S").to_string()
@@ -212,9 +832,29 @@ pub fn find_in_parent_dirs(start: &Path, filename: &str) -> Option<PathBuf> {
     }
 }
 
+/// Exit status for a command interrupted with Ctrl-C (128 + SIGINT)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Exit on the next Ctrl-C. Once `tokio::signal::ctrl_c` has been awaited,
+/// tokio handles SIGINT for the rest of the process and the default
+/// "terminate" is never restored, so a command that listened for it once
+/// calls this to stay interruptible afterwards.
+pub fn exit_on_ctrl_c() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    });
+}
+
+/// Ask a yes/no question. Outside an interactive terminal nobody can answer,
+/// so this fails instead of assuming either answer.
 pub fn confirm(message: &str) -> Result<bool> {
     use dialoguer::Confirm;
 
+    if is_non_interactive() {
+        anyhow::bail!("Confirmation needed, but not running interactively: {message}");
+    }
     Ok(Confirm::new().with_prompt(message).interact()?)
 }
 
@@ -224,6 +864,17 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("120s").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("-5s").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
+
     #[test]
     fn test_prepare_bundle_excludes_venv() {
         let temp_dir = tempdir().unwrap();
@@ -480,4 +1131,359 @@ mod tests {
         // Verify app.R is included (matches **/*.R pattern)
         assert!(relative_paths.contains(&"app.R".to_string()));
     }
+
+    #[test]
+    fn test_plan_bundle_records_skip_reasons() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::create_dir_all(dir_path.join(".venv/lib")).unwrap();
+        fs::write(dir_path.join(".venv/lib/site.py"), "").unwrap();
+        fs::create_dir_all(dir_path.join(".ricochet/data")).unwrap();
+        fs::write(dir_path.join(".ricochet/data/lookup.csv"), "").unwrap();
+        fs::create_dir(dir_path.join("src")).unwrap();
+        fs::write(dir_path.join("src/main.py"), "").unwrap();
+        fs::write(dir_path.join("src/notes.md"), "").unwrap();
+        fs::write(dir_path.join("README.md"), "").unwrap();
+
+        let plan = plan_bundle(
+            dir_path,
+            Some(vec!["src/**".to_string()]),
+            Some(vec!["**/*.md".to_string()]),
+            false,
+        )
+        .unwrap();
+
+        let mut skipped = plan.skipped.clone();
+        skipped.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            skipped,
+            vec![
+                (
+                    PathBuf::from(".ricochet/data/lookup.csv"),
+                    SkipReason::LocalState
+                ),
+                (
+                    PathBuf::from(".venv"),
+                    SkipReason::Builtin {
+                        pattern: ".venv".to_string()
+                    }
+                ),
+                (PathBuf::from("README.md"), SkipReason::NotIncluded),
+                (
+                    PathBuf::from("src/notes.md"),
+                    SkipReason::Excluded {
+                        pattern: "**/*.md".to_string()
+                    }
+                ),
+            ]
+        );
+        assert!(plan.files.contains(&dir_path.join("src/main.py")));
+    }
+
+    #[test]
+    fn test_measure_bundle_matches_create_bundle() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        fs::write(dir_path.join("app.R"), "library(shiny)\n".repeat(100)).unwrap();
+
+        let files = prepare_bundle(dir_path, None, None).unwrap();
+        let (size, digest) = measure_bundle(dir_path, &files, &[]).unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let out = out_dir.path().join("bundle.tar.gz");
+        let written = create_bundle(dir_path, &out, None, None, &[], false, false, None).unwrap();
+
+        assert_eq!(size, fs::metadata(&out).unwrap().len());
+        assert_eq!(digest, written);
+        assert_eq!(digest, file_digest(&out).unwrap());
+    }
+
+    fn bundled_paths(dir: &Path, use_gitignore: bool) -> Vec<String> {
+        plan_bundle(dir, None, None, use_gitignore)
+            .unwrap()
+            .files
+            .iter()
+            .map(|p| p.strip_prefix(dir).unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_bundle_honours_ricochetignore() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::write(
+            dir_path.join(IGNORE_FILE),
+            "node_modules/\n*.csv\n!keep.csv\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir_path.join("node_modules/pkg")).unwrap();
+        fs::write(dir_path.join("node_modules/pkg/index.js"), "").unwrap();
+        fs::write(dir_path.join("dump.csv"), "").unwrap();
+        fs::write(dir_path.join("keep.csv"), "").unwrap();
+        fs::write(dir_path.join("app.R"), "").unwrap();
+
+        let paths = bundled_paths(dir_path, false);
+
+        assert!(paths.contains(&"app.R".to_string()));
+        assert!(paths.contains(&"keep.csv".to_string()));
+        assert!(!paths.contains(&"dump.csv".to_string()));
+        assert!(!paths.iter().any(|p| p.starts_with("node_modules")));
+
+        let plan = plan_bundle(dir_path, None, None, false).unwrap();
+        assert!(plan.skipped.contains(&(
+            PathBuf::from("node_modules"),
+            SkipReason::Ignored {
+                file: PathBuf::from(IGNORE_FILE),
+                pattern: "node_modules/".to_string(),
+            }
+        )));
+    }
+
+    #[test]
+    fn test_plan_bundle_applies_nested_ignore_files_to_their_directory() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::create_dir(dir_path.join("data")).unwrap();
+        fs::write(dir_path.join("data").join(IGNORE_FILE), "/raw.parquet\n").unwrap();
+        fs::write(dir_path.join("data/raw.parquet"), "").unwrap();
+        fs::write(dir_path.join("raw.parquet"), "").unwrap();
+
+        let paths = bundled_paths(dir_path, false);
+
+        assert!(paths.contains(&"raw.parquet".to_string()));
+        assert!(!paths.contains(&"data/raw.parquet".to_string()));
+    }
+
+    #[test]
+    fn test_plan_bundle_gitignore_is_opt_in() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::write(dir_path.join(".gitignore"), ".env\n*.log\n").unwrap();
+        fs::write(dir_path.join(IGNORE_FILE), "!important.log\n").unwrap();
+        fs::write(dir_path.join(".env"), "SECRET=1").unwrap();
+        fs::write(dir_path.join("debug.log"), "").unwrap();
+        fs::write(dir_path.join("important.log"), "").unwrap();
+
+        let paths = bundled_paths(dir_path, false);
+        assert!(paths.contains(&".env".to_string()));
+        assert!(paths.contains(&"debug.log".to_string()));
+
+        let paths = bundled_paths(dir_path, true);
+        assert!(!paths.contains(&".env".to_string()));
+        assert!(!paths.contains(&"debug.log".to_string()));
+        // .ricochetignore wins over .gitignore in the same directory
+        assert!(paths.contains(&"important.log".to_string()));
+    }
+
+    #[test]
+    fn test_plan_bundle_applies_ignore_files_before_include() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::write(dir_path.join(IGNORE_FILE), "secret.R\n").unwrap();
+        fs::write(dir_path.join("app.R"), "").unwrap();
+        fs::write(dir_path.join("secret.R"), "").unwrap();
+
+        let plan = plan_bundle(dir_path, Some(vec!["*.R".to_string()]), None, false).unwrap();
+
+        assert!(plan.files.contains(&dir_path.join("app.R")));
+        assert!(!plan.files.contains(&dir_path.join("secret.R")));
+    }
+
+    #[tokio::test]
+    async fn test_stream_bundle_matches_create_bundle() {
+        use tokio::io::AsyncReadExt;
+
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        fs::write(dir_path.join("app.R"), "library(shiny)\n".repeat(10_000)).unwrap();
+        fs::create_dir(dir_path.join("R")).unwrap();
+        fs::write(dir_path.join("R/utils.R"), "f <- function() 1\n").unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let out = out_dir.path().join("bundle.tar.gz");
+        let written = create_bundle(dir_path, &out, None, None, &[], false, false, None).unwrap();
+
+        let files = prepare_bundle(dir_path, None, None).unwrap();
+        let pb = ProgressBar::hidden();
+        let (mut reader, digest) = stream_bundle(
+            dir_path.to_path_buf(),
+            files.clone(),
+            vec![],
+            Some(pb.clone()),
+        );
+        let mut streamed = Vec::new();
+        reader.read_to_end(&mut streamed).await.unwrap();
+
+        assert_eq!(streamed, fs::read(&out).unwrap());
+        assert_eq!(pb.position(), bundle_source_size(&files, &[]).unwrap());
+        let digest = digest.await.unwrap();
+        assert_eq!(digest.sha256, written);
+        assert_eq!(
+            parse_manifest(&digest.manifest),
+            bundle_manifest(dir_path, &files, &[]).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_bundle_reports_errors_instead_of_truncating() {
+        use tokio::io::AsyncReadExt;
+
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        fs::write(dir_path.join("app.R"), "").unwrap();
+
+        let files = prepare_bundle(dir_path, None, None).unwrap();
+        let missing = vec![(dir_path.join("uv.lock"), "uv.lock".to_string())];
+        let (mut reader, _) = stream_bundle(dir_path.to_path_buf(), files, missing, None);
+        let mut streamed = Vec::new();
+        let err = reader.read_to_end(&mut streamed).await.unwrap_err();
+
+        assert!(err.to_string().contains("uv.lock"), "{err}");
+    }
+
+    fn archive_entries(bundle: &Path) -> Vec<(String, u64, u32, u64)> {
+        let file = File::open(bundle).unwrap();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let header = entry.header();
+                (
+                    entry.path().unwrap().display().to_string(),
+                    header.mtime().unwrap(),
+                    header.mode().unwrap(),
+                    header.uid().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_manifests() {
+        let old = parse_manifest("aaa  app.R\nbbb  R/utils.R\nccc  data/big.csv\n");
+        let new = parse_manifest("aaa  app.R\nddd  R/utils.R\neee  R/new.R\n");
+
+        assert_eq!(
+            diff_manifests(&old, &new),
+            ManifestDiff {
+                added: vec!["R/new.R".to_string()],
+                modified: vec!["R/utils.R".to_string()],
+                removed: vec!["data/big.csv".to_string()],
+            }
+        );
+        assert!(diff_manifests(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_create_bundle_leaves_nothing_behind() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        fs::write(dir_path.join("app.R"), "app").unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let out = out_dir.path().join("app.tar.gz");
+        fs::write(&out, "previous").unwrap();
+
+        let cancel = AtomicBool::new(true);
+        let err = create_bundle(dir_path, &out, None, None, &[], false, false, Some(&cancel))
+            .unwrap_err();
+
+        assert!(err.to_string().contains("cancelled"), "{err}");
+        assert_eq!(fs::read_to_string(&out).unwrap(), "previous");
+        let left: Vec<_> = fs::read_dir(out_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(left, vec!["app.tar.gz"]);
+    }
+
+    #[test]
+    fn test_create_bundle_is_reproducible() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        fs::create_dir(dir_path.join("R")).unwrap();
+        fs::write(dir_path.join("R/b.R"), "b").unwrap();
+        fs::write(dir_path.join("R/a.R"), "a").unwrap();
+        fs::write(dir_path.join("app.R"), "app").unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let first = out_dir.path().join("first.tar.gz");
+        let first_digest =
+            create_bundle(dir_path, &first, None, None, &[], false, false, None).unwrap();
+
+        // Touching a file changes its mtime but not what gets bundled
+        let app = File::options()
+            .append(true)
+            .open(dir_path.join("app.R"))
+            .unwrap();
+        app.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+
+        let second = out_dir.path().join("second.tar.gz");
+        let second_digest =
+            create_bundle(dir_path, &second, None, None, &[], false, false, None).unwrap();
+
+        assert_eq!(first_digest, second_digest);
+        assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+
+        let entries = archive_entries(&first);
+        let names: Vec<&str> = entries.iter().map(|e| e.0.as_str()).collect();
+        assert_eq!(names, vec!["R/a.R", "R/b.R", "app.R", MANIFEST_FILE]);
+        assert!(entries.iter().all(|e| e.3 == 0));
+        assert!(entries.iter().all(|e| e.2 == 0o644));
+        assert!(entries.iter().all(|e| e.1 == 0));
+    }
+
+    #[test]
+    fn test_create_bundle_embeds_manifest_of_checksums() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        fs::write(dir_path.join("app.R"), "hello").unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let out = out_dir.path().join("bundle.tar.gz");
+        create_bundle(dir_path, &out, None, None, &[], false, false, None).unwrap();
+
+        let mut archive =
+            tar::Archive::new(flate2::read::GzDecoder::new(File::open(&out).unwrap()));
+        let mut manifest = String::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            if entry.path().unwrap() == Path::new(MANIFEST_FILE) {
+                entry.read_to_string(&mut manifest).unwrap();
+            }
+        }
+
+        // sha256("hello"), in the format `sha256sum -c` accepts
+        assert_eq!(
+            manifest,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  app.R\n"
+        );
+    }
+
+    #[test]
+    fn test_create_bundle_keeps_a_root_manifest_file() {
+        let temp_dir = tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        // e.g. setuptools' MANIFEST, which is the project's own file
+        fs::write(dir_path.join("MANIFEST"), "mine").unwrap();
+
+        let out_dir = tempdir().unwrap();
+        let out = out_dir.path().join("bundle.tar.gz");
+        create_bundle(dir_path, &out, None, None, &[], false, false, None).unwrap();
+
+        assert_eq!(
+            read_bundle_file(&out, "MANIFEST").unwrap().as_deref(),
+            Some("mine")
+        );
+        let manifest = parse_manifest(&read_bundle_file(&out, MANIFEST_FILE).unwrap().unwrap());
+        assert_eq!(manifest.keys().collect::<Vec<_>>(), vec!["MANIFEST"]);
+    }
 }
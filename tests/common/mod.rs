@@ -0,0 +1,22 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use mockito::Server;
+use url::Url;
+
+/// A config with a single server pointing at `server` and a test API key
+pub fn test_config(server: &Server) -> ricochet_cli::config::Config {
+    ricochet_cli::config::Config::for_test(
+        Url::parse(&server.url()).unwrap(),
+        Some("test_api_key".to_string()),
+    )
+}
+
+/// Accept the key check every command makes before its first request
+pub fn mock_check_key(server: &mut Server) -> mockito::Mock {
+    server
+        .mock("GET", "/api/v0/check_key")
+        .with_status(200)
+        .create()
+}
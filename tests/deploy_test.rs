@@ -1,4 +1,5 @@
 use mockito::{Matcher, Server};
+use ricochet_cli::commands::deploy::DeployOptions;
 use ricochet_cli::config::{Config, ServerConfig};
 use serde_json::json;
 use std::collections::HashMap;
@@ -29,6 +30,7 @@ mod deploy_tests {
             ServerConfig {
                 url: Url::parse(prod_url).unwrap(),
                 api_key: Some("prod_api_key".to_string()),
+                key_fingerprint: None,
             },
         );
         servers.insert(
@@ -36,6 +38,7 @@ mod deploy_tests {
             ServerConfig {
                 url: Url::parse(staging_url).unwrap(),
                 api_key: Some("staging_api_key".to_string()),
+                key_fingerprint: None,
             },
         );
         Config {
@@ -151,10 +154,7 @@ shinyApp(ui = ui, server = server)"#,
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -209,10 +209,7 @@ shinyApp(ui = ui, server = server)"#,
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -227,6 +224,63 @@ shinyApp(ui = ui, server = server)"#,
         assert!(updated_toml.contains(existing_id));
     }
 
+    #[tokio::test]
+    async fn test_deploy_records_bundle_digest_locally() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let existing_id = "01JZA237920RN65T2XHCCV7296";
+        create_test_project(project_path, Some(existing_id)).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _m = server
+            .mock("POST", "/api/v0/content/upload")
+            .with_status(200)
+            .with_body(json!({"id": existing_id, "deployment_id": "dep-1"}).to_string())
+            .expect(2)
+            .create();
+
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let mut digests = Vec::new();
+        for _ in 0..2 {
+            ricochet_cli::commands::deploy::deploy(
+                &config,
+                None,
+                project_path.to_path_buf(),
+                DeployOptions {
+                    force: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+            let log = fs::read_to_string(
+                project_path.join(ricochet_cli::commands::deploy::DEPLOYMENTS_LOG),
+            )
+            .unwrap();
+            let records: Vec<ricochet_cli::commands::deploy::DeploymentRecord> = log
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            // Only the latest deployment of an item to a server is kept
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].content_id, existing_id);
+            assert_eq!(records[0].deployment_id.as_deref(), Some("dep-1"));
+            digests.push(records[0].digest.clone());
+        }
+
+        // The same tree uploads the same bundle, and --dry-run predicts it
+        let report =
+            ricochet_cli::commands::deploy::plan_deploy_bundle(project_path, false).unwrap();
+        assert_eq!(digests[0], digests[1]);
+        assert_eq!(digests[0], report.digest);
+    }
+
     #[tokio::test]
     async fn test_deploy_missing_toml() {
         // Create a temporary directory without _ricochet.toml
@@ -246,10 +300,7 @@ shinyApp(ui = ui, server = server)"#,
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -292,10 +343,7 @@ key = "value"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -332,10 +380,7 @@ key = "value"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -390,10 +435,7 @@ key = "value"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -450,10 +492,7 @@ key = "value"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -500,10 +539,7 @@ key = "value"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -555,10 +591,7 @@ key = "value"
             &config,
             Some("staging"), // Use staging server
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -606,10 +639,7 @@ key = "value"
             &config,
             Some(&mock_url), // Use URL directly
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -659,10 +689,7 @@ key = "value"
             &config,
             None, // No server specified, should use default
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -726,10 +753,7 @@ packages = "uv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -773,10 +797,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -806,10 +827,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -840,10 +858,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -888,10 +903,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -946,10 +958,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -991,10 +1000,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -1017,10 +1023,7 @@ packages = "renv.lock"
             &config,
             Some("nonexistent"),
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -1056,10 +1059,11 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            vec!["SECRET=shh".to_string()],
-            false,
+            DeployOptions {
+                env: vec!["SECRET=shh".to_string()],
+                trust_unpinned_key: true,
+                ..Default::default()
+            },
         )
         .await;
 
@@ -1070,6 +1074,87 @@ packages = "renv.lock"
         _m.assert(); // body matcher confirms the env_vars part was present
     }
 
+    #[tokio::test]
+    async fn test_deploy_new_content_with_from_file_sends_env_vars_field() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+        fs::write(project_path.join("prod.env"), "SECRET=shh\nTOKEN=abc\n").unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _pk = mock_public_key(&mut server);
+        let _m = server
+            .mock("POST", "/api/v0/content/upload")
+            .match_body(Matcher::Regex("name=\"env_vars\"".to_string()))
+            .with_status(200)
+            .with_body(json!({"id": "01JZA237920RN65T2XHCCV7296"}).to_string())
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                env_files: ricochet_cli::env_vars::EnvFiles {
+                    files: vec![project_path.join("prod.env")],
+                    ..Default::default()
+                },
+                trust_unpinned_key: true,
+                yes: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(result.is_ok(), "{result:?}");
+        _m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_from_file_needs_yes_when_not_interactive() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+        fs::write(project_path.join("prod.env"), "SECRET=shh\n").unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = server
+            .mock("POST", "/api/v0/content/upload")
+            .expect(0)
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let err = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                env_files: ricochet_cli::env_vars::EnvFiles {
+                    files: vec![project_path.join("prod.env")],
+                    ..Default::default()
+                },
+                trust_unpinned_key: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert!(format!("{err:#}").contains("--yes"), "{err:#}");
+        upload.assert();
+    }
+
     #[tokio::test]
     async fn test_deploy_without_env_omits_env_vars_field() {
         let temp_dir = TempDir::new().unwrap();
@@ -1097,10 +1182,7 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
-            None,
-            None,
-            Vec::new(),
-            false,
+            DeployOptions::default(),
         )
         .await;
 
@@ -1135,13 +1217,893 @@ packages = "renv.lock"
             &config,
             None,
             project_path.to_path_buf(),
+            DeployOptions {
+                env: vec!["SECRET=shh".to_string()],
+                trust_unpinned_key: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    // ==================== Deploy --wait tests ====================
+
+    const DEPLOYMENT_ID: &str = "01KQZPF4Y5SRHES967VZEYY765";
+
+    fn mock_upload_with_deployment(server: &mut Server) -> mockito::Mock {
+        server
+            .mock("POST", "/api/v0/content/upload")
+            .with_status(200)
+            .with_body(
+                json!({
+                    "id": "01JZA237920RN65T2XHCCV7296",
+                    "deployment_id": DEPLOYMENT_ID
+                })
+                .to_string(),
+            )
+            .create()
+    }
+
+    fn mock_deployment_status(server: &mut Server, status: &str) -> mockito::Mock {
+        server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}", DEPLOYMENT_ID).as_str(),
+            )
+            .with_status(200)
+            .with_body(
+                json!({
+                    "id": DEPLOYMENT_ID,
+                    "content_id": "01JZA237920RN65T2XHCCV7296",
+                    "deployed_at": 1778106471,
+                    "status": status,
+                    "deployed_by": "344509059241640593",
+                    "ip_address": "127.0.0.1",
+                    "requested_ver": null,
+                    "matched_ver": null,
+                    "git_hash": null
+                })
+                .to_string(),
+            )
+            .create()
+    }
+
+    #[tokio::test]
+    async fn test_deploy_wait_succeeds_when_deployment_succeeds() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _m = mock_upload_with_deployment(&mut server);
+        let status = mock_deployment_status(&mut server, "success");
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                wait: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        if let Err(e) = &result {
+            dbg!(&e);
+        }
+        assert!(result.is_ok());
+        status.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_wait_fails_when_deployment_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _m = mock_upload_with_deployment(&mut server);
+        let _status = mock_deployment_status(&mut server, "failure");
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                wait: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(DEPLOYMENT_ID), "{err}");
+        assert!(err.contains("failure"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_deploy_logs_fetches_build_log_and_waits() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _m = mock_upload_with_deployment(&mut server);
+        let _status = mock_deployment_status(&mut server, "success");
+        let logs = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}/logs", DEPLOYMENT_ID).as_str(),
+            )
+            .with_status(200)
+            .with_body("Restoring renv library...\n")
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
             None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                logs: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        if let Err(e) = &result {
+            dbg!(&e);
+        }
+        assert!(result.is_ok());
+        logs.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_wait_without_deployment_id_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _m = server
+            .mock("POST", "/api/v0/content/upload")
+            .with_status(200)
+            .with_body(json!({"id": "01JZA237920RN65T2XHCCV7296"}).to_string())
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
             None,
-            vec!["SECRET=shh".to_string()],
-            false,
+            project_path.to_path_buf(),
+            DeployOptions {
+                wait: true,
+                ..Default::default()
+            },
         )
         .await;
 
-        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("deployment ID"), "{err}");
+    }
+
+    // ==================== Skipping unchanged deploys ====================
+
+    const EXISTING_ID: &str = "01JZA237920RN65T2XHCCV7296";
+
+    fn mock_upload_existing(server: &mut Server, hits: usize) -> mockito::Mock {
+        server
+            .mock("POST", "/api/v0/content/upload")
+            .with_status(200)
+            .with_body(json!({"id": EXISTING_ID, "deployment_id": "dep-1"}).to_string())
+            .expect(hits)
+            .create()
+    }
+
+    fn mock_existing_deployment_status(server: &mut Server, status: &str) -> mockito::Mock {
+        server
+            .mock("GET", "/api/v0/content/deployments/dep-1")
+            .with_status(200)
+            .with_body(
+                json!({
+                    "id": "dep-1",
+                    "content_id": EXISTING_ID,
+                    "deployed_at": 1778106471,
+                    "status": status,
+                    "deployed_by": "344509059241640593",
+                    "ip_address": "127.0.0.1",
+                    "requested_ver": null,
+                    "matched_ver": null,
+                    "git_hash": null
+                })
+                .to_string(),
+            )
+            .create()
+    }
+
+    async fn deploy_project(config: &Config, path: &Path, force: bool) {
+        ricochet_cli::commands::deploy::deploy(
+            config,
+            None,
+            path.to_path_buf(),
+            DeployOptions {
+                force,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_skips_unchanged_bundle_unless_forced() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 2);
+        let _status = mock_existing_deployment_status(&mut server, "success");
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        deploy_project(&config, project_path, false).await;
+        deploy_project(&config, project_path, false).await;
+        deploy_project(&config, project_path, true).await;
+
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_with_env_file_uploads_unchanged_bundle() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+        fs::write(project_path.join("prod.env"), "SECRET=shh\n").unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _pk = mock_public_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 2);
+        let _status = mock_existing_deployment_status(&mut server, "success");
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        deploy_project(&config, project_path, false).await;
+        ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                env_files: ricochet_cli::env_vars::EnvFiles {
+                    files: vec![project_path.join("prod.env")],
+                    ..Default::default()
+                },
+                trust_unpinned_key: true,
+                yes: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_compares_against_the_recorded_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 2);
+        let _status = mock_existing_deployment_status(&mut server, "success");
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        deploy_project(&config, project_path, false).await;
+        deploy_project(&config, project_path, false).await;
+
+        // Without the per-file checksums there is nothing to compare with
+        fs::remove_dir_all(project_path.join(ricochet_cli::commands::deploy::MANIFESTS_DIR))
+            .unwrap();
+        deploy_project(&config, project_path, false).await;
+
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_retries_unchanged_bundle_whose_build_failed() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 2);
+        let _status = mock_existing_deployment_status(&mut server, "failure");
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        // Without --wait the outcome is checked with the server before skipping
+        deploy_project(&config, project_path, false).await;
+        deploy_project(&config, project_path, false).await;
+
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_wait_does_not_record_a_failed_deployment() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 1);
+        let _status = mock_existing_deployment_status(&mut server, "failure");
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                wait: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(
+            !project_path
+                .join(ricochet_cli::commands::deploy::DEPLOYMENTS_LOG)
+                .exists()
+        );
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_redeploys_after_source_or_config_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 3);
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        deploy_project(&config, project_path, false).await;
+        fs::write(project_path.join("app.R"), "shinyApp(ui, server)").unwrap();
+        deploy_project(&config, project_path, false).await;
+        let toml_path = project_path.join("_ricochet.toml");
+        let toml = fs::read_to_string(&toml_path).unwrap();
+        fs::write(&toml_path, toml.replace("test-app", "renamed-app")).unwrap();
+        deploy_project(&config, project_path, false).await;
+
+        upload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deploy_redeploys_after_workspace_root_file_change() {
+        let workspace = TempDir::new().unwrap();
+        fs::write(workspace.path().join("uv.lock"), "version = 1").unwrap();
+        fs::write(workspace.path().join(".python-version"), "3.12").unwrap();
+        let project_path = workspace.path().join("app");
+        fs::create_dir(&project_path).unwrap();
+        create_python_project(&project_path).unwrap();
+        let toml_path = project_path.join("_ricochet.toml");
+        let toml = fs::read_to_string(&toml_path).unwrap();
+        fs::write(
+            &toml_path,
+            toml.replace(
+                "[content]\n",
+                &format!("[content]\nid = \"{EXISTING_ID}\"\n"),
+            ),
+        )
+        .unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 2);
+        let _status = mock_existing_deployment_status(&mut server, "success");
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        deploy_project(&config, &project_path, false).await;
+        deploy_project(&config, &project_path, false).await;
+        fs::write(workspace.path().join("uv.lock"), "version = 2").unwrap();
+        deploy_project(&config, &project_path, false).await;
+
+        upload.assert();
+    }
+
+    // ==================== Deploy --diff tests ====================
+
+    #[tokio::test]
+    async fn test_deploy_records_manifest_for_diff() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, Some(EXISTING_ID)).unwrap();
+        fs::create_dir(project_path.join("data")).unwrap();
+        fs::write(project_path.join("data/big.csv"), "a,b\n1,2\n").unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = mock_upload_existing(&mut server, 2);
+        let config = Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        deploy_project(&config, project_path, false).await;
+
+        let log =
+            fs::read_to_string(project_path.join(ricochet_cli::commands::deploy::DEPLOYMENTS_LOG))
+                .unwrap();
+        let record: ricochet_cli::commands::deploy::DeploymentRecord =
+            serde_json::from_str(log.lines().last().unwrap()).unwrap();
+        let manifest = fs::read_to_string(
+            project_path
+                .join(ricochet_cli::commands::deploy::MANIFESTS_DIR)
+                .join(record.digest.trim_start_matches("sha256:")),
+        )
+        .unwrap();
+        let previous = ricochet_cli::utils::parse_manifest(&manifest);
+        assert!(previous.contains_key("app.R"));
+        assert!(previous.contains_key("data/big.csv"));
+
+        fs::write(project_path.join("app.R"), "shinyApp(ui, server)").unwrap();
+        fs::write(project_path.join("helpers.R"), "f <- function() 1").unwrap();
+        fs::remove_file(project_path.join("data/big.csv")).unwrap();
+
+        let files = ricochet_cli::utils::prepare_bundle(project_path, None, None).unwrap();
+        let current = ricochet_cli::utils::bundle_manifest(project_path, &files, &[]).unwrap();
+        let diff = ricochet_cli::utils::diff_manifests(&previous, &current);
+        assert_eq!(diff.modified, vec!["app.R"]);
+        assert_eq!(diff.added, vec!["helpers.R"]);
+        assert_eq!(diff.removed, vec!["data/big.csv"]);
+
+        // Without a terminal there is no prompt, so the deploy goes ahead
+        ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                diff: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        upload.assert();
+        // Only the latest bundle's manifest is kept
+        let manifests: Vec<_> =
+            fs::read_dir(project_path.join(ricochet_cli::commands::deploy::MANIFESTS_DIR))
+                .unwrap()
+                .collect();
+        assert_eq!(manifests.len(), 1);
+    }
+
+    // ==================== Deploy --dry-run tests ====================
+
+    #[test]
+    fn test_dry_run_reports_bundled_and_skipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+        fs::create_dir_all(project_path.join(".renv/library")).unwrap();
+        fs::write(project_path.join(".renv/library/cache"), "cache").unwrap();
+
+        let report =
+            ricochet_cli::commands::deploy::plan_deploy_bundle(project_path, false).unwrap();
+
+        let paths: Vec<&str> = report.files.iter().map(|f| f.path.as_str()).collect();
+        assert!(paths.contains(&"_ricochet.toml"));
+        assert!(paths.contains(&"renv.lock"));
+        assert!(paths.contains(&"app.R"));
+        assert!(!paths.iter().any(|p| p.starts_with(".renv")));
+
+        // The blacklisted directory is reported once, not file by file
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].path, ".renv");
+
+        assert_eq!(
+            report.total_size,
+            report.files.iter().map(|f| f.size).sum::<u64>()
+        );
+        assert!(report.compressed_size > 0);
+    }
+
+    #[test]
+    fn test_dry_run_includes_workspace_root_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path();
+        let project_path = workspace.join("app");
+        fs::create_dir(&project_path).unwrap();
+        create_python_project(&project_path).unwrap();
+        fs::write(workspace.join("uv.lock"), "lock").unwrap();
+        fs::write(workspace.join(".python-version"), "3.12").unwrap();
+
+        let report =
+            ricochet_cli::commands::deploy::plan_deploy_bundle(&project_path, false).unwrap();
+
+        let uv_lock = report
+            .files
+            .iter()
+            .find(|f| f.path == "uv.lock")
+            .expect("uv.lock should be bundled from the workspace root");
+        assert_eq!(uv_lock.size, 4);
+        assert!(uv_lock.source.is_some());
+        assert!(report.files.iter().any(|f| f.path == ".python-version"));
+    }
+
+    #[test]
+    fn test_dry_run_json_names_the_exclude_rule() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        create_test_project(project_path, None).unwrap();
+        let toml_path = project_path.join("_ricochet.toml");
+        let toml = fs::read_to_string(&toml_path)
+            .unwrap()
+            .replace("[content]\n", "[content]\nexclude = [\"data/**\"]\n");
+        fs::write(&toml_path, toml).unwrap();
+        fs::create_dir(project_path.join("data")).unwrap();
+        fs::write(project_path.join("data/big.csv"), "a,b\n1,2\n").unwrap();
+
+        let report =
+            ricochet_cli::commands::deploy::plan_deploy_bundle(project_path, false).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(
+            json["skipped"],
+            json!([{"path": "data/big.csv", "rule": "excluded", "pattern": "data/**"}])
+        );
+        assert!(
+            !json["files"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|f| f["path"] == "data/big.csv")
+        );
+    }
+
+    #[test]
+    fn test_dry_run_without_ricochet_toml_fails() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = ricochet_cli::commands::deploy::plan_deploy_bundle(temp_dir.path(), false);
+
+        assert!(result.unwrap_err().to_string().contains("_ricochet.toml"));
+    }
+
+    // ==================== Bundle and deploy --bundle tests ====================
+
+    #[tokio::test]
+    async fn test_bundle_writes_archive_with_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("app");
+        fs::create_dir(&project_path).unwrap();
+        create_test_project(&project_path, None).unwrap();
+        let output = temp_dir.path().join("app.tar.gz");
+
+        ricochet_cli::commands::bundle::bundle(&project_path, &output, false, false)
+            .await
+            .unwrap();
+
+        let config = ricochet_cli::utils::read_bundle_config(&output).unwrap();
+        assert_eq!(
+            config,
+            fs::read_to_string(project_path.join("_ricochet.toml")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_embeds_config_left_out_by_include() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("app");
+        fs::create_dir(&project_path).unwrap();
+        create_test_project(&project_path, None).unwrap();
+        let toml_path = project_path.join("_ricochet.toml");
+        let toml = fs::read_to_string(&toml_path).unwrap().replace(
+            "[content]\n",
+            "[content]\ninclude = [\"*.R\", \"renv.lock\"]\n",
+        );
+        fs::write(&toml_path, &toml).unwrap();
+        let output = temp_dir.path().join("app.tar.gz");
+
+        ricochet_cli::commands::bundle::bundle(&project_path, &output, false, false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ricochet_cli::utils::read_bundle_config(&output).unwrap(),
+            toml
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_into_content_directory_keeps_previous_until_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_project(temp_dir.path(), None).unwrap();
+        let output = temp_dir.path().join("app.tar.gz");
+
+        ricochet_cli::commands::bundle::bundle(temp_dir.path(), &output, false, false)
+            .await
+            .unwrap();
+        let first = fs::read(&output).unwrap();
+        ricochet_cli::commands::bundle::bundle(temp_dir.path(), &output, false, false)
+            .await
+            .unwrap();
+
+        // The first bundle is neither packed into the second nor changes it
+        assert!(
+            ricochet_cli::utils::read_bundle_file(&output, "app.tar.gz")
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(fs::read(&output).unwrap(), first);
+    }
+
+    #[tokio::test]
+    async fn test_bundle_without_ricochet_toml_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("app.tar.gz");
+
+        let result =
+            ricochet_cli::commands::bundle::bundle(temp_dir.path(), &output, false, false).await;
+
+        assert!(result.unwrap_err().to_string().contains("_ricochet.toml"));
+        assert!(!output.exists());
+    }
+
+    #[tokio::test]
+    async fn test_deploy_prebuilt_bundle_uploads_embedded_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().join("app");
+        fs::create_dir(&project_path).unwrap();
+        create_test_project(&project_path, Some("01JZA237920RN65T2XHCCV7296")).unwrap();
+        let output = temp_dir.path().join("app.tar.gz");
+        ricochet_cli::commands::bundle::bundle(&project_path, &output, false, false)
+            .await
+            .unwrap();
+        // The source directory is not needed once the bundle exists
+        fs::remove_dir_all(&project_path).unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let upload = server
+            .mock("POST", "/api/v0/content/upload")
+            .match_header("authorization", "Key test_api_key")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex(r#"name="bundle""#.to_string()),
+                Matcher::Regex(r#"name="config""#.to_string()),
+                Matcher::Regex(r#"name="id"\r\n\r\n01JZA237920RN65T2XHCCV7296"#.to_string()),
+            ]))
+            .with_status(200)
+            .with_body(json!({"id": "01JZA237920RN65T2XHCCV7296"}).to_string())
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            temp_dir.path().to_path_buf(),
+            DeployOptions {
+                bundle: Some(output),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        if let Err(e) = &result {
+            dbg!(&e);
+        }
+        assert!(result.is_ok());
+        upload.assert();
+        // The bundle may be deployed from anywhere, so nothing is recorded here
+        assert!(
+            !temp_dir
+                .path()
+                .join(ricochet_cli::commands::deploy::DEPLOYMENTS_LOG)
+                .exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deploy_bundle_without_config_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("src");
+        fs::create_dir(&source).unwrap();
+        fs::write(source.join("app.R"), "library(shiny)").unwrap();
+        let output = temp_dir.path().join("app.tar.gz");
+        ricochet_cli::utils::create_bundle(&source, &output, None, None, &[], false, false, None)
+            .unwrap();
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            temp_dir.path().to_path_buf(),
+            DeployOptions {
+                bundle: Some(output),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No _ricochet.toml found")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deploy_rolls_back_when_smoke_check_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let existing_id = "01JZA237920RN65T2XHCCV7296";
+        let new_deployment = "01JZA300000000000000000NEW";
+        let previous_deployment = "01JZA200000000000000000OLD";
+        let rollback_deployment = "01JZA400000000000000000RBK";
+        create_test_project(project_path, Some(existing_id)).unwrap();
+
+        let deployment = |id: &str, deployed_at: i64| {
+            json!({
+                "id": id,
+                "content_id": existing_id,
+                "deployed_at": deployed_at,
+                "status": "success",
+                "deployed_by": "344509059241640593",
+                "ip_address": "127.0.0.1",
+                "requested_ver": "4.5.1",
+                "matched_ver": "4.5.2",
+                "git_hash": null
+            })
+        };
+
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _upload = server
+            .mock("POST", "/api/v0/content/upload")
+            .with_status(200)
+            .with_body(json!({"id": existing_id, "deployment_id": new_deployment}).to_string())
+            .create();
+        let _status = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{new_deployment}").as_str(),
+            )
+            .with_status(200)
+            .with_body(deployment(new_deployment, 1778106471).to_string())
+            .create();
+        let _health = server
+            .mock("GET", format!("/apps/{existing_id}/health").as_str())
+            .with_status(502)
+            .create();
+        let _history = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{existing_id}/deployments").as_str(),
+            )
+            .with_status(200)
+            .with_body(
+                json!([
+                    deployment(new_deployment, 1778106471),
+                    deployment(previous_deployment, 1778000000)
+                ])
+                .to_string(),
+            )
+            .create();
+        let rollback = server
+            .mock(
+                "POST",
+                format!("/api/v0/content/{existing_id}/deployments/{previous_deployment}/rollback")
+                    .as_str(),
+            )
+            .with_status(200)
+            .with_body(deployment(rollback_deployment, 1778200000).to_string())
+            .create();
+        let _rollback_status = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{rollback_deployment}").as_str(),
+            )
+            .with_status(200)
+            .with_body(deployment(rollback_deployment, 1778200000).to_string())
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        let result = ricochet_cli::commands::deploy::deploy(
+            &config,
+            None,
+            project_path.to_path_buf(),
+            DeployOptions {
+                smoke: Some(ricochet_cli::app::smoke::SmokeCheck {
+                    path: "/health".to_string(),
+                    expect_status: 200,
+                    timeout: std::time::Duration::ZERO,
+                }),
+                rollback_on_failure: true,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(
+            err.downcast_ref::<ricochet_cli::app::smoke::SmokeCheckFailed>()
+                .is_some()
+        );
+        rollback.assert();
+        // The record must not claim the rolled-back bundle is deployed
+        assert!(
+            !project_path
+                .join(ricochet_cli::commands::deploy::DEPLOYMENTS_LOG)
+                .exists()
+        );
     }
 }
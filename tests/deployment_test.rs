@@ -1,3 +1,6 @@
+mod common;
+
+use common::{mock_check_key, test_config};
 use mockito::Server;
 use serde_json::json;
 use url::Url;
@@ -231,4 +234,383 @@ mod deployment_tests {
         assert!(json_str.contains("success"));
         assert!(json_str.contains("4.5.1"));
     }
+
+    // --- get_deployment_logs ---
+
+    #[tokio::test]
+    async fn test_get_deployment_logs_success() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}/logs", DEPLOYMENT_ULID).as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("Restoring renv library...\nInstalled 12 packages\n")
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let server_config = config.resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let log = client.get_deployment_logs(DEPLOYMENT_ULID).await.unwrap();
+
+        assert_eq!(log, "Restoring renv library...\nInstalled 12 packages\n");
+    }
+
+    #[tokio::test]
+    async fn test_get_deployment_logs_not_found() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}/logs", DEPLOYMENT_ULID).as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(404)
+            .with_body("Deployment not found")
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let server_config = config.resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let result = client.get_deployment_logs(DEPLOYMENT_ULID).await;
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Deployment not found")
+        );
+    }
+
+    fn mock_logs(server: &mut Server, range: &str, status: usize, body: &str) -> mockito::Mock {
+        server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}/logs", DEPLOYMENT_ULID).as_str(),
+            )
+            .match_header("range", range)
+            .with_status(status)
+            .with_body(body)
+            .create()
+    }
+
+    #[tokio::test]
+    async fn test_get_deployment_logs_from_asks_for_the_new_bytes() {
+        let mut server = Server::new_async().await;
+        let _m = mock_logs(&mut server, "bytes=26-", 206, "Installed 12 packages\n");
+
+        let config = test_config(&server);
+        let client =
+            ricochet_cli::client::RicochetClient::new(&config.resolve_server(None).unwrap())
+                .unwrap();
+        let (start, new) = client
+            .get_deployment_logs_from(DEPLOYMENT_ULID, 26)
+            .await
+            .unwrap();
+
+        assert_eq!(start, 26);
+        assert_eq!(new, b"Installed 12 packages\n");
+    }
+
+    #[tokio::test]
+    async fn test_get_deployment_logs_from_handles_servers_without_ranges() {
+        let mut server = Server::new_async().await;
+        let log = "Restoring renv library...\nInstalled 12 packages\n";
+        let _full = mock_logs(&mut server, "bytes=26-", 200, log);
+        let _shorter = mock_logs(&mut server, "bytes=100-", 200, log);
+        let _none_yet = mock_logs(&mut server, "bytes=48-", 416, "");
+
+        let config = test_config(&server);
+        let client =
+            ricochet_cli::client::RicochetClient::new(&config.resolve_server(None).unwrap())
+                .unwrap();
+
+        // The whole log is trimmed to what comes after the offset
+        let (start, new) = client
+            .get_deployment_logs_from(DEPLOYMENT_ULID, 26)
+            .await
+            .unwrap();
+        assert_eq!(
+            (start, new.as_slice()),
+            (26, "Installed 12 packages\n".as_bytes())
+        );
+
+        // A log shorter than the offset was rewritten and starts over
+        let (start, new) = client
+            .get_deployment_logs_from(DEPLOYMENT_ULID, 100)
+            .await
+            .unwrap();
+        assert_eq!((start, new.as_slice()), (0, log.as_bytes()));
+
+        let (start, new) = client
+            .get_deployment_logs_from(DEPLOYMENT_ULID, 48)
+            .await
+            .unwrap();
+        assert_eq!((start, new.len()), (48, 0));
+    }
+
+    #[tokio::test]
+    async fn test_follow_deployment_logs_fails_for_a_failed_deployment() {
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let mut failed = deployment_json();
+        failed["status"] = json!("failure");
+        let _d = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}", DEPLOYMENT_ULID).as_str(),
+            )
+            .with_status(200)
+            .with_body(failed.to_string())
+            .create();
+        let _logs = mock_logs(&mut server, "bytes=0-", 200, "renv restore failed\n");
+
+        let config = test_config(&server);
+        let result =
+            ricochet_cli::item::deployment::deployment_logs(&config, None, DEPLOYMENT_ULID, true)
+                .await;
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(DEPLOYMENT_ULID), "{err}");
+        assert!(err.contains("failure"), "{err}");
+    }
+
+    // --- rollback ---
+
+    const PREVIOUS_ULID: &str = "01KQZN0000000000000000PREV";
+    const ROLLBACK_ULID: &str = "01KQZQ0000000000000000ROLL";
+
+    fn history_json() -> serde_json::Value {
+        let mut live = deployment_json();
+        live["deployed_at"] = json!(1778106471);
+        let mut previous = deployment_json();
+        previous["id"] = json!(PREVIOUS_ULID);
+        previous["deployed_at"] = json!(1778000000);
+        json!([live, previous])
+    }
+
+    fn mock_history(server: &mut Server) -> mockito::Mock {
+        server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{}/deployments", CONTENT_ULID).as_str(),
+            )
+            .with_status(200)
+            .with_body(history_json().to_string())
+            .create()
+    }
+
+    fn mock_rollback(server: &mut Server, target: &str) -> mockito::Mock {
+        let mut rolled_back = deployment_json();
+        rolled_back["id"] = json!(ROLLBACK_ULID);
+        rolled_back["status"] = json!("pending");
+        server
+            .mock(
+                "POST",
+                format!(
+                    "/api/v0/content/{}/deployments/{}/rollback",
+                    CONTENT_ULID, target
+                )
+                .as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_body(rolled_back.to_string())
+            .create()
+    }
+
+    #[tokio::test]
+    async fn test_rollback_defaults_to_previous_success() {
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _history = mock_history(&mut server);
+        let rollback = mock_rollback(&mut server, PREVIOUS_ULID);
+
+        ricochet_cli::item::deployment::rollback(
+            &test_config(&server),
+            None,
+            CONTENT_ULID,
+            None,
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+
+        rollback.assert();
+    }
+
+    #[tokio::test]
+    async fn test_rollback_wait_reports_the_new_deployment() {
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _history = mock_history(&mut server);
+        let _rollback = mock_rollback(&mut server, PREVIOUS_ULID);
+        let mut failed = deployment_json();
+        failed["id"] = json!(ROLLBACK_ULID);
+        failed["status"] = json!("failure");
+        let status = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/deployments/{}", ROLLBACK_ULID).as_str(),
+            )
+            .with_status(200)
+            .with_body(failed.to_string())
+            .create();
+
+        let result = ricochet_cli::item::deployment::rollback(
+            &test_config(&server),
+            None,
+            CONTENT_ULID,
+            None,
+            true,
+            true,
+        )
+        .await;
+
+        status.assert();
+        assert!(result.unwrap_err().to_string().contains(ROLLBACK_ULID));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_to_unknown_deployment_fails() {
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _history = mock_history(&mut server);
+
+        let result = ricochet_cli::item::deployment::rollback(
+            &test_config(&server),
+            None,
+            CONTENT_ULID,
+            Some("01KQZ000000000000000MISSING"),
+            true,
+            false,
+        )
+        .await;
+
+        assert!(result.unwrap_err().to_string().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_without_earlier_success_fails() {
+        let mut server = Server::new_async().await;
+        let _ck = mock_check_key(&mut server);
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{}/deployments", CONTENT_ULID).as_str(),
+            )
+            .with_status(200)
+            .with_body(json!([deployment_json()]).to_string())
+            .create();
+
+        let result = ricochet_cli::item::deployment::rollback(
+            &test_config(&server),
+            None,
+            CONTENT_ULID,
+            None,
+            true,
+            false,
+        )
+        .await;
+
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No earlier successful deployment")
+        );
+    }
+
+    // --- smoke checks ---
+
+    fn smoke(timeout: std::time::Duration) -> ricochet_cli::app::smoke::SmokeCheck {
+        ricochet_cli::app::smoke::SmokeCheck {
+            path: "/health".to_string(),
+            expect_status: 200,
+            timeout,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_smoke_check_passes() {
+        let mut server = Server::new_async().await;
+        let health = server
+            .mock("GET", format!("/apps/{}/health", CONTENT_ULID).as_str())
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .create();
+
+        let server_config = test_config(&server).resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let result = ricochet_cli::app::smoke::smoke_check(
+            &client,
+            CONTENT_ULID,
+            &smoke(std::time::Duration::from_secs(5)),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        health.assert();
+    }
+
+    #[tokio::test]
+    async fn test_smoke_check_does_not_follow_redirects() {
+        let mut server = Server::new_async().await;
+        let _health = server
+            .mock("GET", format!("/apps/{}/health", CONTENT_ULID).as_str())
+            .with_status(302)
+            .with_header("location", "/login")
+            .create();
+        let login = server
+            .mock("GET", "/login")
+            .with_status(200)
+            .expect(0)
+            .create();
+
+        let server_config = test_config(&server).resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let status = client.check_app(CONTENT_ULID, "/health").await.unwrap();
+
+        assert_eq!(status.as_u16(), 302);
+        login.assert();
+    }
+
+    #[tokio::test]
+    async fn test_smoke_check_times_out() {
+        let mut server = Server::new_async().await;
+        let _health = server
+            .mock("GET", format!("/apps/{}/health", CONTENT_ULID).as_str())
+            .with_status(503)
+            .create();
+
+        let server_config = test_config(&server).resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let err = ricochet_cli::app::smoke::smoke_check(
+            &client,
+            CONTENT_ULID,
+            &smoke(std::time::Duration::ZERO),
+        )
+        .await
+        .unwrap_err();
+
+        let failed = err
+            .downcast_ref::<ricochet_cli::app::smoke::SmokeCheckFailed>()
+            .unwrap();
+        assert!(failed.last.contains("503"));
+    }
 }
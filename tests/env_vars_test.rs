@@ -1,5 +1,6 @@
 use mockito::{Matcher, Server};
 use ricochet_cli::OutputFormat;
+use ricochet_cli::env_vars::EnvFiles;
 use serde_json::json;
 use url::Url;
 
@@ -156,6 +157,94 @@ async fn test_delete_env_var_not_found() {
     _m.assert_async().await;
 }
 
+// --- diff ---
+
+fn mock_server_names(server: &mut Server, names: &[&str]) -> mockito::Mock {
+    server
+        .mock(
+            "GET",
+            format!("/api/v0/content/{CONTENT_ID}/env-vars").as_str(),
+        )
+        .match_header("authorization", "Key test_api_key")
+        .with_status(200)
+        .with_body(json!(names).to_string())
+        .create()
+}
+
+#[tokio::test]
+async fn test_diff_env_vars_fails_with_exit_code_when_server_is_missing_names() {
+    let mut server = Server::new_async().await;
+    let _check_key_mock = mock_valid_key(&mut server);
+    let _m = mock_server_names(&mut server, &["API_KEY", "OLD_TOKEN"]);
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let toml_path = dir.path().join("_ricochet.toml");
+    std::fs::write(
+        &toml_path,
+        format!(
+            r#"[content]
+id = "{CONTENT_ID}"
+name = "test-app"
+content_type = "shiny"
+entrypoint = "app.R"
+access_type = "private"
+
+[language]
+name = "r"
+packages = "renv.lock"
+
+[env_vars]
+REGION = "eu"
+"#
+        ),
+    )
+    .unwrap();
+    std::fs::write(dir.path().join(".env"), "API_KEY=x\nSENTRY_DSN=y\n").unwrap();
+
+    let config = test_config(&server);
+    let result = ricochet_cli::item::env_vars::diff_env_vars(
+        &config,
+        None,
+        None,
+        Some(&toml_path),
+        &[],
+        true,
+        OutputFormat::Json,
+    )
+    .await;
+
+    let err = result.unwrap_err();
+    let drift = err
+        .downcast_ref::<ricochet_cli::item::env_vars::EnvVarsDrift>()
+        .expect("drift error");
+    assert_eq!(drift.names, vec!["REGION", "SENTRY_DSN"]);
+}
+
+#[tokio::test]
+async fn test_diff_env_vars_passes_when_only_extra_names() {
+    let mut server = Server::new_async().await;
+    let _check_key_mock = mock_valid_key(&mut server);
+    let _m = mock_server_names(&mut server, &["API_KEY", "OLD_TOKEN"]);
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("prod.env");
+    std::fs::write(&file, "API_KEY=x\n").unwrap();
+
+    let config = test_config(&server);
+    let result = ricochet_cli::item::env_vars::diff_env_vars(
+        &config,
+        None,
+        Some(CONTENT_ID),
+        None,
+        &[file],
+        true,
+        OutputFormat::Table,
+    )
+    .await;
+
+    assert!(result.is_ok(), "expected success, got {result:?}");
+}
+
 // --- set ---
 
 #[tokio::test]
@@ -187,6 +276,9 @@ async fn test_set_env_vars_success() {
         Some(CONTENT_ID),
         None,
         &["API_KEY=secret".to_string()],
+        &EnvFiles::default(),
+        false,
+        true,
         OutputFormat::Table,
     )
     .await;
@@ -196,6 +288,127 @@ async fn test_set_env_vars_success() {
     _set_mock.assert_async().await;
 }
 
+#[tokio::test]
+async fn test_set_env_vars_sends_envelope_when_server_accepts_it() {
+    let mut server = Server::new_async().await;
+    let _check_key_mock = mock_valid_key(&mut server);
+
+    let _key_mock = server
+        .mock("GET", "/api/v0/public-key")
+        .with_status(200)
+        .with_header("x-ricochet-env-vars-versions", "1, 2")
+        .with_body(TEST_PUB_PEM)
+        .create();
+
+    let _set_mock = server
+        .mock(
+            "PATCH",
+            format!("/api/v0/content/{CONTENT_ID}/env-vars").as_str(),
+        )
+        .match_header("authorization", "Key test_api_key")
+        .match_body(Matcher::PartialJson(json!({"version": 2})))
+        .with_status(200)
+        .with_body(json!(["JWT"]).to_string())
+        .create();
+
+    // Too long for RSA-OAEP alone
+    let jwt = format!("JWT={}", "x".repeat(1000));
+    let config = test_config(&server);
+    let result = ricochet_cli::item::env_vars::set_env_vars(
+        &config,
+        None,
+        Some(CONTENT_ID),
+        None,
+        &[jwt],
+        &EnvFiles::default(),
+        false,
+        true,
+        OutputFormat::Table,
+    )
+    .await;
+
+    assert!(result.is_ok(), "expected success, got {result:?}");
+    _set_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_set_env_vars_from_file() {
+    let mut server = Server::new_async().await;
+    let _check_key_mock = mock_valid_key(&mut server);
+
+    let _key_mock = server
+        .mock("GET", "/api/v0/public-key")
+        .with_status(200)
+        .with_body(TEST_PUB_PEM)
+        .create();
+
+    let _set_mock = server
+        .mock(
+            "PATCH",
+            format!("/api/v0/content/{CONTENT_ID}/env-vars").as_str(),
+        )
+        .match_header("authorization", "Key test_api_key")
+        .with_status(200)
+        .with_body(json!(["DB_URL", "DB_POOL"]).to_string())
+        .create();
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("prod.env");
+    std::fs::write(&file, "DB_URL=postgres://db\nDB_POOL=5\nDB_PASSWORD=shh\n").unwrap();
+
+    let config = test_config(&server);
+    let result = ricochet_cli::item::env_vars::set_env_vars(
+        &config,
+        None,
+        Some(CONTENT_ID),
+        None,
+        &[],
+        &EnvFiles {
+            files: vec![file],
+            only: vec!["DB_*".to_string()],
+            except: vec!["*PASSWORD".to_string()],
+        },
+        true,
+        true,
+        OutputFormat::Table,
+    )
+    .await;
+
+    assert!(result.is_ok(), "expected success, got {result:?}");
+    _set_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_set_env_vars_from_file_with_nothing_left_sends_nothing() {
+    let mut server = Server::new_async().await;
+    let _set_mock = server.mock("PATCH", Matcher::Any).expect(0).create();
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join(".env");
+    std::fs::write(&file, "API_KEY=secret\n").unwrap();
+
+    let config = test_config(&server);
+    let result = ricochet_cli::item::env_vars::set_env_vars(
+        &config,
+        None,
+        Some(CONTENT_ID),
+        None,
+        &[],
+        &EnvFiles {
+            files: vec![file],
+            only: vec!["DB_*".to_string()],
+            ..Default::default()
+        },
+        true,
+        true,
+        OutputFormat::Table,
+    )
+    .await;
+
+    assert!(result.is_err());
+    _set_mock.assert_async().await;
+}
+
 #[tokio::test]
 async fn test_set_env_vars_rejects_server_error() {
     let mut server = Server::new_async().await;
@@ -224,6 +437,9 @@ async fn test_set_env_vars_rejects_server_error() {
         Some(CONTENT_ID),
         None,
         &["BAD KEY=secret".to_string()],
+        &EnvFiles::default(),
+        false,
+        true,
         OutputFormat::Table,
     )
     .await;
@@ -263,6 +479,8 @@ async fn test_replace_env_vars_success() {
         Some(CONTENT_ID),
         None,
         &["DATABASE_URL=postgres://localhost".to_string()],
+        &EnvFiles::default(),
+        true,
         true,
         OutputFormat::Table,
     )
@@ -301,6 +519,8 @@ async fn test_replace_env_vars_not_found() {
         Some(CONTENT_ID),
         None,
         &["DATABASE_URL=postgres://localhost".to_string()],
+        &EnvFiles::default(),
+        true,
         true,
         OutputFormat::Table,
     )
@@ -1,3 +1,6 @@
+mod common;
+
+use common::{mock_check_key, test_config};
 use mockito::Server;
 use serde_json::json;
 use url::Url;
@@ -303,4 +306,279 @@ mod instances_tests {
         assert!(json_str.contains("instance_id"));
         assert!(json_str.contains("connections"));
     }
+
+    #[tokio::test]
+    async fn test_get_instance_logs() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let pid = "01KPXMKQ8N9XCBQ06ZV33JFXCD";
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{content_id}/instances/{pid}/logs").as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_body(
+                json!([
+                    {"timestamp": "2026-04-23T17:01:14Z", "stream": "stdout", "line": "Listening on 0.0.0.0:8000"},
+                    {"timestamp": "2026-04-23T17:01:15Z", "stream": "stderr", "line": "Warning: deprecated"}
+                ])
+                .to_string(),
+            )
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        let server_config = config.resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let lines = client
+            .get_instance_logs(content_id, pid, None)
+            .await
+            .unwrap();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].line, "Listening on 0.0.0.0:8000");
+        assert_eq!(lines[1].stream, ricochet_cli::app::logs::LogStream::Stderr);
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_logs_since() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let pid = "01KPXMKQ8N9XCBQ06ZV33JFXCD";
+
+        let m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{content_id}/instances/{pid}/logs").as_str(),
+            )
+            .match_query(mockito::Matcher::UrlEncoded(
+                "since".into(),
+                "2026-04-23T17:00:00Z".into(),
+            ))
+            .with_status(200)
+            .with_body("[]")
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        let server_config = config.resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let since = "2026-04-23T17:00:00Z".parse().unwrap();
+        let lines = client
+            .get_instance_logs(content_id, pid, Some(since))
+            .await
+            .unwrap();
+
+        assert!(lines.is_empty());
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_instance_logs_not_found() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let pid = "nonexistent";
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{content_id}/instances/{pid}/logs").as_str(),
+            )
+            .with_status(404)
+            .with_body(json!({"error": "Instance not found"}).to_string())
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        let server_config = config.resolve_server(None).unwrap();
+        let client = ricochet_cli::client::RicochetClient::new(&server_config).unwrap();
+        let result = client.get_instance_logs(content_id, pid, None).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logs_follow_fails_when_a_running_instance_errors() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let pid = "01KPXMKQ8N9XCBQ06ZV33JFXCD";
+
+        let _k = mock_check_key(&mut server);
+        let _list = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{content_id}/instances").as_str(),
+            )
+            .with_status(200)
+            .with_body(json!([{"instance_id": pid, "connections": 0}]).to_string())
+            .create();
+        let _logs = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{content_id}/instances/{pid}/logs").as_str(),
+            )
+            .with_status(403)
+            .with_body(json!({"error": "Forbidden"}).to_string())
+            .create();
+
+        let result = ricochet_cli::app::logs::logs(
+            &test_config(&server),
+            None,
+            Some(content_id),
+            None,
+            None,
+            true,
+            None,
+            None,
+            ricochet_cli::OutputFormat::Table,
+        )
+        .await;
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("403"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_rolling_restart_waits_for_replacements() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let path = format!("/api/v0/content/{content_id}/instances");
+        let _ck = mock_check_key(&mut server);
+
+        // Mocks are matched in creation order until they are used up
+        let _before = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(json!([{"instance_id": "a"}, {"instance_id": "b"}]).to_string())
+            .expect(1)
+            .create();
+        let _first = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(json!([{"instance_id": "b"}, {"instance_id": "c"}]).to_string())
+            .expect(1)
+            .create();
+        let _second = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(json!([{"instance_id": "c"}, {"instance_id": "d"}]).to_string())
+            .create();
+        let stop_a = server
+            .mock("POST", format!("{path}/a/stop").as_str())
+            .with_status(200)
+            .create();
+        let stop_b = server
+            .mock("POST", format!("{path}/b/stop").as_str())
+            .with_status(200)
+            .create();
+
+        ricochet_cli::app::instances::restart_instances(
+            &test_config(&server),
+            None,
+            Some(content_id),
+            None,
+            true,
+            1,
+            std::time::Duration::from_secs(30),
+        )
+        .await
+        .unwrap();
+
+        stop_a.assert();
+        stop_b.assert();
+    }
+
+    #[tokio::test]
+    async fn test_rolling_restart_ignores_earlier_replacements() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let path = format!("/api/v0/content/{content_id}/instances");
+        let _ck = mock_check_key(&mut server);
+
+        let _before = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(json!([{"instance_id": "a"}, {"instance_id": "b"}]).to_string())
+            .expect(1)
+            .create();
+        // "c" replaces "a" while the autoscaler starts "x"
+        let _first = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(
+                json!([{"instance_id": "b"}, {"instance_id": "c"}, {"instance_id": "x"}])
+                    .to_string(),
+            )
+            .expect(1)
+            .create();
+        // Nothing replaces "b"
+        let _second = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(json!([{"instance_id": "c"}, {"instance_id": "x"}]).to_string())
+            .create();
+        let _stop_a = server
+            .mock("POST", format!("{path}/a/stop").as_str())
+            .with_status(200)
+            .create();
+        let _stop_b = server
+            .mock("POST", format!("{path}/b/stop").as_str())
+            .with_status(200)
+            .create();
+
+        let err = ricochet_cli::app::instances::restart_instances(
+            &test_config(&server),
+            None,
+            Some(content_id),
+            None,
+            true,
+            1,
+            std::time::Duration::ZERO,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("No replacement for b"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_restart_times_out_without_replacement() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let path = format!("/api/v0/content/{content_id}/instances");
+        let _ck = mock_check_key(&mut server);
+
+        let _list = server
+            .mock("GET", path.as_str())
+            .with_status(200)
+            .with_body(json!([{"instance_id": "a"}]).to_string())
+            .create();
+        let _stop = server
+            .mock("POST", format!("{path}/a/stop").as_str())
+            .with_status(200)
+            .create();
+
+        let err = ricochet_cli::app::instances::restart_instances(
+            &test_config(&server),
+            None,
+            Some(content_id),
+            None,
+            false,
+            1,
+            std::time::Duration::ZERO,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("No replacement for a"));
+    }
 }
@@ -0,0 +1,210 @@
+use mockito::Server;
+use serde_json::json;
+use url::Url;
+
+#[cfg(test)]
+mod invocation_tests {
+    use super::*;
+
+    const CONTENT_ULID: &str = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+    const INVOCATION_ULID: &str = "01JSZB123456789ABCDEFGHIJ";
+
+    fn invocation_json() -> serde_json::Value {
+        json!({
+            "id": INVOCATION_ULID,
+            "content_id": CONTENT_ULID,
+            "status": "success",
+            "started_at": 1778106471,
+            "ended_at": 1778106531,
+            "trigger": "schedule",
+            "exit_code": 0
+        })
+    }
+
+    fn client(server: &Server) -> ricochet_cli::client::RicochetClient {
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        let server_config = config.resolve_server(None).unwrap();
+        ricochet_cli::client::RicochetClient::new(&server_config).unwrap()
+    }
+
+    // --- list_invocations ---
+
+    #[tokio::test]
+    async fn test_list_invocations_success() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{}/invocations", CONTENT_ULID).as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_body(json!([invocation_json(), {"id": "01JSZC0000000000000000000", "status": "running"}]).to_string())
+            .create();
+
+        let invocations = client(&server)
+            .list_invocations(CONTENT_ULID)
+            .await
+            .unwrap();
+
+        assert_eq!(invocations.len(), 2);
+        assert_eq!(invocations[0].id, INVOCATION_ULID);
+        assert_eq!(invocations[0].trigger.as_deref(), Some("schedule"));
+        assert_eq!(invocations[1].status, "running");
+        assert_eq!(invocations[1].ended_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_invocations_unauthorized() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{}/invocations", CONTENT_ULID).as_str(),
+            )
+            .with_status(401)
+            .with_body(json!({"error": "Unauthorized"}).to_string())
+            .create();
+
+        let result = client(&server).list_invocations(CONTENT_ULID).await;
+
+        assert!(result.is_err());
+    }
+
+    // --- get_invocation ---
+
+    #[tokio::test]
+    async fn test_get_invocation_success() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "GET",
+                format!(
+                    "/api/v0/content/{}/invocations/{}",
+                    CONTENT_ULID, INVOCATION_ULID
+                )
+                .as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_body(invocation_json().to_string())
+            .create();
+
+        let invocation: ricochet_cli::item::invocation::InvocationRow = serde_json::from_value(
+            client(&server)
+                .get_invocation(CONTENT_ULID, INVOCATION_ULID)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(invocation.id, INVOCATION_ULID);
+        assert_eq!(invocation.exit_code, Some(0));
+
+        let json = serde_json::to_value(&invocation).unwrap();
+        assert_eq!(json["started_at"], 1778106471);
+    }
+
+    // --- get_invocation_logs ---
+
+    #[tokio::test]
+    async fn test_get_invocation_logs_success() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "GET",
+                format!(
+                    "/api/v0/content/{}/invocations/{}/logs",
+                    CONTENT_ULID, INVOCATION_ULID
+                )
+                .as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("Rendering report.qmd\nOutput created: report.html\n")
+            .create();
+
+        let log = client(&server)
+            .get_invocation_logs(CONTENT_ULID, INVOCATION_ULID)
+            .await
+            .unwrap();
+
+        assert_eq!(log, "Rendering report.qmd\nOutput created: report.html\n");
+    }
+
+    // --- stop_invocation ---
+
+    #[tokio::test]
+    async fn test_stop_invocation_command() {
+        let mut server = Server::new_async().await;
+
+        let _ck = server
+            .mock("GET", "/api/v0/check_key")
+            .with_status(200)
+            .create();
+        let m = server
+            .mock(
+                "POST",
+                format!(
+                    "/api/v0/content/{}/invocations/{}/stop",
+                    CONTENT_ULID, INVOCATION_ULID
+                )
+                .as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        ricochet_cli::item::invocation::stop_invocation(
+            &config,
+            None,
+            CONTENT_ULID,
+            INVOCATION_ULID,
+        )
+        .await
+        .unwrap();
+
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_stop_invocation_not_running() {
+        let mut server = Server::new_async().await;
+
+        let _m = server
+            .mock(
+                "POST",
+                format!(
+                    "/api/v0/content/{}/invocations/{}/stop",
+                    CONTENT_ULID, INVOCATION_ULID
+                )
+                .as_str(),
+            )
+            .with_status(409)
+            .with_body("Invocation is not running")
+            .create();
+
+        let result = client(&server)
+            .stop_invocation(CONTENT_ULID, INVOCATION_ULID)
+            .await;
+
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invocation is not running")
+        );
+    }
+}
@@ -220,4 +220,198 @@ mod invoke_tests {
         assert!(yaml_str.contains("running"));
         assert!(yaml_str.contains("content_id"));
     }
+
+    #[tokio::test]
+    async fn test_invoke_sends_resolved_params() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+
+        let _ck = server
+            .mock("GET", "/api/v0/check_key")
+            .with_status(200)
+            .create();
+        let m = server
+            .mock(
+                "POST",
+                format!("/api/v0/content/{}/invoke", content_id).as_str(),
+            )
+            .match_body(Matcher::Json(json!({"n": 10, "region": "eu"})))
+            .with_status(200)
+            .with_body(json!({"invocation_id": "01JSZB123456789ABCDEFGHIJ"}).to_string())
+            .create();
+
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+
+        let params = ricochet_cli::item::invoke::resolve_params(
+            &["n=10".to_string()],
+            Some(r#"{"n": 1, "region": "eu"}"#),
+            None,
+        )
+        .unwrap();
+        ricochet_cli::item::invoke::invoke(
+            &config,
+            None,
+            content_id,
+            params,
+            false,
+            false,
+            None,
+            ricochet_cli::OutputFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        m.assert();
+    }
+
+    const INVOCATION_ID: &str = "01JSZB123456789ABCDEFGHIJ";
+
+    /// Mock a task whose invocation ends with `finished`.
+    fn mock_invocation(
+        server: &mut Server,
+        content_id: &str,
+        finished: serde_json::Value,
+    ) -> Vec<mockito::Mock> {
+        vec![
+            server
+                .mock("GET", "/api/v0/check_key")
+                .with_status(200)
+                .create(),
+            server
+                .mock(
+                    "POST",
+                    format!("/api/v0/content/{}/invoke", content_id).as_str(),
+                )
+                .with_status(200)
+                .with_body(json!({"invocation_id": INVOCATION_ID, "status": "running"}).to_string())
+                .create(),
+            server
+                .mock(
+                    "GET",
+                    format!(
+                        "/api/v0/content/{}/invocations/{}",
+                        content_id, INVOCATION_ID
+                    )
+                    .as_str(),
+                )
+                .with_status(200)
+                .with_body(finished.to_string())
+                .create(),
+        ]
+    }
+
+    async fn invoke_and_wait(
+        server: &Server,
+        content_id: &str,
+        follow: bool,
+        timeout: Option<std::time::Duration>,
+    ) -> anyhow::Result<()> {
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        ricochet_cli::item::invoke::invoke(
+            &config,
+            None,
+            content_id,
+            None,
+            true,
+            follow,
+            timeout,
+            ricochet_cli::OutputFormat::Json,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_invoke_wait_succeeds_when_invocation_succeeds() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let _mocks = mock_invocation(&mut server, content_id, json!({"status": "success"}));
+
+        invoke_and_wait(&server, content_id, false, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invoke_wait_fails_with_task_exit_code() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let _mocks = mock_invocation(
+            &mut server,
+            content_id,
+            json!({"status": "failed", "exit_code": 3}),
+        );
+
+        let err = invoke_and_wait(&server, content_id, false, None)
+            .await
+            .unwrap_err();
+        let failed = err
+            .downcast_ref::<ricochet_cli::item::invoke::InvocationFailed>()
+            .unwrap();
+        assert_eq!(failed.code, 3);
+        assert_eq!(failed.status, "failed");
+    }
+
+    #[tokio::test]
+    async fn test_invoke_follow_fetches_output_and_reports_stop() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let _mocks = mock_invocation(&mut server, content_id, json!({"status": "stopped"}));
+        let logs = server
+            .mock(
+                "GET",
+                format!(
+                    "/api/v0/content/{}/invocations/{}/logs",
+                    content_id, INVOCATION_ID
+                )
+                .as_str(),
+            )
+            .with_status(200)
+            .with_body("rendering report...\n")
+            .create();
+
+        let err = invoke_and_wait(&server, content_id, true, None)
+            .await
+            .unwrap_err();
+        let failed = err
+            .downcast_ref::<ricochet_cli::item::invoke::InvocationFailed>()
+            .unwrap();
+        assert_eq!(failed.code, ricochet_cli::item::invoke::STOPPED_EXIT_CODE);
+        logs.assert();
+    }
+
+    #[tokio::test]
+    async fn test_invoke_wait_fails_without_a_status() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let _mocks = mock_invocation(&mut server, content_id, json!({"exit_code": null}));
+
+        let err = invoke_and_wait(&server, content_id, false, None)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("no status"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_invoke_wait_gives_up_after_timeout() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let _mocks = mock_invocation(&mut server, content_id, json!({"status": "running"}));
+
+        let err = invoke_and_wait(&server, content_id, false, Some(std::time::Duration::ZERO))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("still running"), "{err}");
+        assert!(
+            err.downcast_ref::<ricochet_cli::item::invoke::InvocationFailed>()
+                .is_none()
+        );
+    }
 }
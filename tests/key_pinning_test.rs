@@ -0,0 +1,203 @@
+use mockito::Server;
+use ricochet_cli::client::RicochetClient;
+use ricochet_cli::config::Config;
+use ricochet_cli::env_vars::EnvFiles;
+use ricochet_cli::item::env_vars::pinned_server_key;
+use serial_test::serial;
+use std::env;
+use tempfile::TempDir;
+use url::Url;
+
+const TEST_PUB_PEM: &str = "-----BEGIN RSA PUBLIC KEY-----
+MIIBCgKCAQEAr1XuDE4bFt7TnYqAtiRQ9RvC2sG3s8N8zUsCvhM+mZD7mGTN47bk
+vYxKvp5ShVnM/6XZeCfRQA2TKXnf6dWsRgcZcBMufKHfN9VLNxawLMKHddceHlLA
+rFTwsPE9rU9p5p5uA6zhUnZk/skzWumqZw9WK7Lztbh6fhX9UMYXvaBzCFF1nfTM
+kGl7YkRcwfL4p+1oa7uGFYaRxvBKv6q9/hm7W9Em7H0g4+icc85wkvlzJrghKakp
+5wDkaY8XmSGSiOZr0U8/fPBC4SASPuT5Hy17zZwu7SEYW31JYnRvFoo8bF8N3QxT
+WigXLNxbQJjhAq7Y6mU8h7yF2zWMbFGMqwIDAQAB
+-----END RSA PUBLIC KEY-----
+";
+
+fn test_key_fingerprint() -> String {
+    let key = ricochet_cli::crypto::parse_public_key_pem(TEST_PUB_PEM).unwrap();
+    ricochet_cli::crypto::fingerprint(&key).unwrap()
+}
+
+fn mock_public_key(server: &mut Server) -> mockito::Mock {
+    server
+        .mock("GET", "/api/v0/public-key")
+        .with_status(200)
+        .with_body(TEST_PUB_PEM)
+        .create()
+}
+
+/// Point HOME at a fresh directory holding a config with one server, "prod",
+/// at the mock server's URL.
+fn save_config(server: &Server, key_fingerprint: Option<&str>) -> (Config, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("HOME", temp_dir.path());
+    }
+
+    let mut config = Config::for_test(
+        Url::parse(&server.url()).unwrap(),
+        Some("test_api_key".to_string()),
+    );
+    let mut prod = config.servers.remove("default").unwrap();
+    prod.key_fingerprint = key_fingerprint.map(str::to_string);
+    config.servers.insert("prod".to_string(), prod);
+    config.default_server = Some("prod".to_string());
+    config.save().unwrap();
+
+    (config, temp_dir)
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn pins_the_key_on_first_use() {
+    let mut server = Server::new_async().await;
+    let _m = mock_public_key(&mut server);
+    let (config, _home) = save_config(&server, None);
+
+    let client = RicochetClient::new(&config.servers["prod"]).unwrap();
+    pinned_server_key(&client, false).await.unwrap();
+
+    let saved = Config::load().unwrap();
+    assert_eq!(
+        saved.servers["prod"].key_fingerprint,
+        Some(test_key_fingerprint())
+    );
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn accepts_the_pinned_key() {
+    let mut server = Server::new_async().await;
+    let _m = mock_public_key(&mut server);
+    let (config, _home) = save_config(&server, Some(&test_key_fingerprint()));
+
+    let client = RicochetClient::new(&config.servers["prod"]).unwrap();
+    let key = pinned_server_key(&client, false).await.unwrap();
+
+    assert_eq!(key.fingerprint, test_key_fingerprint());
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn refuses_a_changed_key() {
+    let mut server = Server::new_async().await;
+    let _m = mock_public_key(&mut server);
+    let (config, _home) = save_config(&server, Some("SHA256:previous"));
+
+    let client = RicochetClient::new(&config.servers["prod"]).unwrap();
+    let err = pinned_server_key(&client, false)
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("has changed"), "{err}");
+    assert!(err.contains("SHA256:previous"), "{err}");
+    assert!(err.contains("ricochet server trust-key prod"), "{err}");
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn checks_the_pin_saved_on_disk() {
+    let mut server = Server::new_async().await;
+    let _m = mock_public_key(&mut server);
+    // The command's config predates the pin another command saved since
+    let (config, _home) = save_config(&server, None);
+    let mut saved = Config::load().unwrap();
+    saved.servers.get_mut("prod").unwrap().key_fingerprint = Some("SHA256:previous".to_string());
+    saved.save().unwrap();
+
+    let client = RicochetClient::new(&config.servers["prod"]).unwrap();
+    let err = pinned_server_key(&client, false)
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("has changed"), "{err}");
+    assert_eq!(
+        Config::load().unwrap().servers["prod"]
+            .key_fingerprint
+            .as_deref(),
+        Some("SHA256:previous")
+    );
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn refuses_an_unconfigured_server_unless_trusted() {
+    let mut server = Server::new_async().await;
+    let _m = mock_public_key(&mut server);
+    let (mut config, _home) = save_config(&server, None);
+    let prod = config.servers.remove("prod").unwrap();
+    config.default_server = None;
+    config.save().unwrap();
+
+    let client = RicochetClient::new(&prod).unwrap();
+    let err = pinned_server_key(&client, false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--trust-unpinned-key"), "{err}");
+
+    let key = pinned_server_key(&client, true).await.unwrap();
+    assert_eq!(key.fingerprint, test_key_fingerprint());
+    assert!(Config::load().unwrap().servers.is_empty());
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn set_env_vars_sends_nothing_when_the_key_changed() {
+    let mut server = Server::new_async().await;
+    let _check_key = server
+        .mock("GET", "/api/v0/check_key")
+        .with_status(200)
+        .create();
+    let _m = mock_public_key(&mut server);
+    let set_mock = server
+        .mock("PATCH", mockito::Matcher::Any)
+        .expect(0)
+        .create();
+    let (config, _home) = save_config(&server, Some("SHA256:previous"));
+
+    let result = ricochet_cli::item::env_vars::set_env_vars(
+        &config,
+        None,
+        Some("01K66JV2Q123456789ABCDEF"),
+        None,
+        &["API_KEY=secret".to_string()],
+        &EnvFiles::default(),
+        false,
+        false,
+        ricochet_cli::OutputFormat::Table,
+    )
+    .await;
+
+    assert!(result.is_err());
+    set_mock.assert_async().await;
+}
+
+#[tokio::test]
+#[serial(env_tests)]
+async fn trust_key_replaces_the_pin() {
+    let mut server = Server::new_async().await;
+    let _m = mock_public_key(&mut server);
+    let (mut config, _home) = save_config(&server, Some("SHA256:previous"));
+
+    ricochet_cli::commands::server::trust_key(&mut config, "prod".to_string(), true)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        config.servers["prod"].key_fingerprint,
+        Some(test_key_fingerprint())
+    );
+    let saved = Config::load().unwrap();
+    assert_eq!(
+        saved.servers["prod"].key_fingerprint,
+        Some(test_key_fingerprint())
+    );
+}
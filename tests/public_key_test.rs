@@ -26,9 +26,31 @@ async fn fetches_and_parses_public_key() {
     let cfg = ServerConfig {
         url: Url::parse(&server.url()).unwrap(),
         api_key: Some("test_api_key".to_string()),
+        key_fingerprint: None,
     };
     let client = RicochetClient::new(&cfg).unwrap();
 
-    let key = client.get_public_key().await;
+    let key = client.fetch_public_key().await;
     assert!(key.is_ok(), "expected a parsed key, got {key:?}");
 }
+
+#[tokio::test]
+async fn reads_accepted_payload_versions() {
+    let mut server = Server::new_async().await;
+    let _m = server
+        .mock("GET", "/api/v0/public-key")
+        .with_status(200)
+        .with_header("x-ricochet-env-vars-versions", "1, 2")
+        .with_body(TEST_PUB_PEM)
+        .create();
+
+    let cfg = ServerConfig {
+        url: Url::parse(&server.url()).unwrap(),
+        api_key: Some("test_api_key".to_string()),
+        key_fingerprint: None,
+    };
+    let client = RicochetClient::new(&cfg).unwrap();
+
+    let key = client.fetch_public_key().await.unwrap();
+    assert!(key.supports_envelope);
+}
@@ -171,6 +171,123 @@ mod schedule_tests {
 
     // These tests validate the cron parsing logic that runs before the API call.
     // They don't need a mock server since they fail locally.
+    fn client_for(server: &Server) -> ricochet_cli::client::RicochetClient {
+        let config = ricochet_cli::config::Config::for_test(
+            Url::parse(&server.url()).unwrap(),
+            Some("test_api_key".to_string()),
+        );
+        let server_config = config.resolve_server(None).unwrap();
+        ricochet_cli::client::RicochetClient::new(&server_config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_schedule() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{}/schedule", content_id).as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(200)
+            .with_body(json!({"schedule": "0 9 * * 1-5", "paused": true}).to_string())
+            .create();
+
+        let schedule = client_for(&server).get_schedule(content_id).await.unwrap();
+        assert_eq!(schedule.schedule.as_deref(), Some("0 9 * * 1-5"));
+        assert!(schedule.paused);
+    }
+
+    #[tokio::test]
+    async fn test_get_schedule_unset() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+
+        let _m = server
+            .mock(
+                "GET",
+                format!("/api/v0/content/{}/schedule", content_id).as_str(),
+            )
+            .with_status(200)
+            .with_body(json!({"schedule": null}).to_string())
+            .create();
+
+        let schedule = client_for(&server).get_schedule(content_id).await.unwrap();
+        assert!(schedule.schedule.is_none());
+        assert!(!schedule.paused);
+    }
+
+    #[tokio::test]
+    async fn test_clear_schedule() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+
+        let m = server
+            .mock(
+                "DELETE",
+                format!("/api/v0/content/{}/schedule", content_id).as_str(),
+            )
+            .match_header("authorization", "Key test_api_key")
+            .with_status(204)
+            .create();
+
+        client_for(&server)
+            .clear_schedule(content_id)
+            .await
+            .unwrap();
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn test_clear_schedule_not_found() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+
+        let _m = server
+            .mock(
+                "DELETE",
+                format!("/api/v0/content/{}/schedule", content_id).as_str(),
+            )
+            .with_status(404)
+            .with_body(json!({"error": "Content not found"}).to_string())
+            .create();
+
+        let err = client_for(&server)
+            .clear_schedule(content_id)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Failed to clear schedule"));
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_schedule() {
+        let mut server = Server::new_async().await;
+        let content_id = "01JSZAXZ3TSTAYXP56ARDVFJCJ";
+        let client = client_for(&server);
+
+        for paused in [true, false] {
+            let m = server
+                .mock(
+                    "PATCH",
+                    format!("/api/v0/content/{}/schedule", content_id).as_str(),
+                )
+                .match_header("authorization", "Key test_api_key")
+                .match_body(mockito::Matcher::Json(json!({"paused": paused})))
+                .with_status(200)
+                .with_body(json!({"schedule": "0 9 * * 1-5", "paused": paused}).to_string())
+                .create();
+
+            let schedule = client
+                .set_schedule_paused(content_id, paused)
+                .await
+                .unwrap();
+            assert_eq!(schedule.paused, paused);
+            m.assert();
+        }
+    }
+
     mod cron_validation {
         use std::str::FromStr;
 
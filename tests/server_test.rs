@@ -41,6 +41,7 @@ fn create_multi_server_config() -> Config {
         ServerConfig {
             url: Url::parse("https://prod.ricochet.com").unwrap(),
             api_key: Some("rico_prod_key".to_string()),
+            key_fingerprint: None,
         },
     );
     servers.insert(
@@ -48,6 +49,7 @@ fn create_multi_server_config() -> Config {
         ServerConfig {
             url: Url::parse("https://staging.ricochet.com").unwrap(),
             api_key: Some("rico_staging_key".to_string()),
+            key_fingerprint: None,
         },
     );
     servers.insert(
@@ -55,6 +57,7 @@ fn create_multi_server_config() -> Config {
         ServerConfig {
             url: Url::parse("http://localhost:3000").unwrap(),
             api_key: None,
+            key_fingerprint: None,
         },
     );
     Config {
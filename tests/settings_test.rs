@@ -1,3 +1,6 @@
+mod common;
+
+use common::test_config;
 use mockito::Server;
 use serde_json::json;
 use url::Url;
@@ -73,3 +76,151 @@ async fn test_update_settings_not_found() {
     let result = client.update_settings(content_id, &patch).await;
     assert!(result.is_err());
 }
+
+mod scale {
+    use super::*;
+    use mockito::Matcher;
+    use ricochet_cli::app::scale::{Scale, scale};
+
+    const CONTENT_ID: &str = "01KE52BY41EQ7NE89K7Z5MMZ84";
+
+    const REMOTE_TOML: &str = r#"
+[content]
+id = "01KE52BY41EQ7NE89K7Z5MMZ84"
+name = "example-app"
+entrypoint = "app.R"
+access_type = "private"
+content_type = "shiny"
+
+[language]
+name = "r"
+packages = "renv.lock"
+
+[serve]
+min_instances = 0
+max_instances = 5
+spawn_threshold = 80
+max_connections = 10
+"#;
+
+    fn mock_remote(server: &mut Server) -> (mockito::Mock, mockito::Mock) {
+        let check_key = server
+            .mock("GET", "/api/v0/check_key")
+            .with_status(200)
+            .create();
+        let toml = server
+            .mock("GET", format!("/api/v0/content/{CONTENT_ID}/toml").as_str())
+            .with_status(200)
+            .with_body(REMOTE_TOML)
+            .create();
+        (check_key, toml)
+    }
+
+    #[tokio::test]
+    async fn test_scale_sends_only_serve_group() {
+        let mut server = Server::new_async().await;
+        let _remote = mock_remote(&mut server);
+        let patch = server
+            .mock(
+                "PATCH",
+                format!("/api/v0/content/{CONTENT_ID}/settings").as_str(),
+            )
+            .match_body(Matcher::Json(
+                json!({"serve": {"min_instances": 2, "max_instances": 6}}),
+            ))
+            .with_status(200)
+            .create();
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("_ricochet.toml");
+        scale(
+            &test_config(&server),
+            None,
+            Some(CONTENT_ID),
+            Some(&missing),
+            Scale {
+                min_instances: Some(2),
+                max_instances: Some(6),
+                spawn_threshold: None,
+            },
+            true,
+            false,
+            ricochet_cli::OutputFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        patch.assert();
+    }
+
+    #[tokio::test]
+    async fn test_scale_writes_local_toml() {
+        let mut server = Server::new_async().await;
+        let _remote = mock_remote(&mut server);
+        let _patch = server
+            .mock(
+                "PATCH",
+                format!("/api/v0/content/{CONTENT_ID}/settings").as_str(),
+            )
+            .with_status(200)
+            .create();
+
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("_ricochet.toml");
+        std::fs::write(&toml_path, REMOTE_TOML).unwrap();
+
+        scale(
+            &test_config(&server),
+            None,
+            None,
+            Some(&toml_path),
+            Scale {
+                min_instances: None,
+                max_instances: Some(8),
+                spawn_threshold: Some(60),
+            },
+            true,
+            true,
+            ricochet_cli::OutputFormat::Json,
+        )
+        .await
+        .unwrap();
+
+        let written = std::fs::read_to_string(&toml_path).unwrap();
+        assert!(written.contains("min_instances = 0\nmax_instances = 8\nspawn_threshold = 60\n"));
+        assert!(written.contains("access_type = \"private\""));
+    }
+
+    #[tokio::test]
+    async fn test_scale_rejects_min_above_max() {
+        let mut server = Server::new_async().await;
+        let _remote = mock_remote(&mut server);
+        let patch = server
+            .mock(
+                "PATCH",
+                format!("/api/v0/content/{CONTENT_ID}/settings").as_str(),
+            )
+            .expect(0)
+            .create();
+
+        let err = scale(
+            &test_config(&server),
+            None,
+            Some(CONTENT_ID),
+            None,
+            Scale {
+                min_instances: Some(7),
+                max_instances: None,
+                spawn_threshold: None,
+            },
+            true,
+            false,
+            ricochet_cli::OutputFormat::Json,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("cannot be greater than"));
+        patch.assert();
+    }
+}